@@ -1,19 +1,27 @@
-use chrono::Weekday;
+use chrono_tz::Tz;
 
-pub const OPEN_AI_COMPLETIONS_ENDPOINT: &str = "https://api.openai.com/v1/chat/completions";
+pub const OPEN_AI_BASE_URL: &str = "https://api.openai.com/v1";
+pub const DEFAULT_OPEN_AI_MODEL: &str = "gpt-4o-mini";
+pub const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com/v1";
+pub const DEFAULT_ANTHROPIC_MODEL: &str = "claude-sonnet-4-5";
 pub const BING_SEARCH_ENDPOINT: &str = "https://api.bing.microsoft.com/v7.0/search";
+pub const BRAVE_SEARCH_ENDPOINT: &str = "https://api.search.brave.com/res/v1/web/search";
 pub const PERPLEXITY_SEARCH_ENDPOINT: &str = "https://api.perplexity.ai/chat/completions";
 
 pub struct CustomEmail {
     pub topic: &'static str,
     pub subject: &'static str,
-    pub schedule: &'static [Weekday],
-    pub send_to: &'static str,
+    /// Cron expression, see `models::digest_models::Schedule::Cron`.
+    pub schedule: &'static str,
+    pub timezone: Tz,
+    pub send_to: &'static [&'static str],
 }
 
+/// Seeded into the digest store the first time it is created; manage digests via `/api/digests` after that.
 pub const CUSTOM_EMAILS: [CustomEmail; 1] = [CustomEmail {
     topic: "Retrieve the latest funding & grant programs for anything related to non-profit AI, Indigenous/Endangered languages or Australian Indigenous funding.",
     subject: "Ourland: New potential funding opportunities",
-    schedule: &[Weekday::Sun, Weekday::Tue, Weekday::Thu],
-    send_to: "devon@land.org.au",
+    schedule: "0 0 7 * * Sun,Tue,Thu",
+    timezone: Tz::Australia__Sydney,
+    send_to: &["devon@land.org.au"],
 }];
@@ -13,3 +13,8 @@ pub fn log_error(error: &str) {
         eprintln!("{:?}", error);
     }
 }
+
+pub fn data_path(file_name: &str) -> std::path::PathBuf {
+    let data_dir = std::env::var("DATA_DIR").unwrap_or_else(|_| "data".to_string());
+    std::path::Path::new(&data_dir).join(file_name)
+}
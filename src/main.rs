@@ -1,12 +1,20 @@
 use actix_web::middleware::Logger;
 use actix_web::{get, post, web, App, HttpResponse, HttpServer, Responder};
+use constants::utility::data_path;
 use dotenv::dotenv;
 use env_logger::Env;
+use providers::Providers;
+use services::email::Mailer;
+use storage::{digest_store::DigestStore, job_store::JobStore, run_store::RunStore};
 
 mod constants;
 mod middleware;
 mod models;
+mod providers;
 mod routes;
+mod scheduler;
+mod services;
+mod storage;
 
 #[get("/")]
 async fn hello() -> impl Responder {
@@ -27,18 +35,73 @@ async fn main() -> std::io::Result<()> {
         .expect("PORT must be a valid number");
     env_logger::init_from_env(Env::default().default_filter_or("info"));
 
-    HttpServer::new(|| {
+    let digest_store = web::Data::new(
+        DigestStore::load(data_path("digests.json")).expect("Failed to load digest store"),
+    );
+    let run_store =
+        web::Data::new(RunStore::load(data_path("runs.json")).expect("Failed to load run store"));
+    let job_store = web::Data::new(JobStore::default());
+    let providers = web::Data::new(Providers::from_env().expect("Invalid provider configuration"));
+    let mailer = web::Data::new(Mailer::from_env().expect("Invalid SMTP configuration"));
+    let email_limit = services::email::max_request_bytes();
+
+    if std::env::var("SCHEDULER_ENABLED").unwrap_or_default() != "false" {
+        actix_web::rt::spawn(scheduler::runner::start(
+            digest_store.clone(),
+            run_store.clone(),
+            job_store.clone(),
+            providers.clone(),
+            mailer.clone(),
+        ));
+    }
+
+    HttpServer::new(move || {
         App::new()
+            .app_data(digest_store.clone())
+            .app_data(run_store.clone())
+            .app_data(job_store.clone())
+            .app_data(providers.clone())
+            .app_data(mailer.clone())
             .wrap(middleware::guard_middleware::ApiKeyMiddleware)
             .wrap(Logger::new("%a %{User-Agent}i %r %s %b %T")) // Single, more detailed logger
             .service(web::scope("/api").configure(|r| {
-                r.route("/email", web::post().to(routes::email::send_email));
-                r.route("/search", web::post().to(routes::bing::search));
+                r.service(
+                    web::resource("/email")
+                        .app_data(web::JsonConfig::default().limit(email_limit))
+                        .route(web::post().to(routes::email::send_email)),
+                );
+                r.route("/search", web::post().to(routes::search::search));
                 r.route("/transform", web::post().to(routes::open_ai::transform));
                 r.route(
                     "/perplexity",
                     web::post().to(routes::perplexity::search_and_transform),
                 );
+                r.route("/digests", web::get().to(routes::digests::list_digests));
+                r.route("/digests", web::post().to(routes::digests::create_digest));
+                r.route("/digests/{id}", web::get().to(routes::digests::get_digest));
+                r.route(
+                    "/digests/{id}",
+                    web::put().to(routes::digests::update_digest),
+                );
+                r.route(
+                    "/digests/{id}",
+                    web::delete().to(routes::digests::delete_digest),
+                );
+                r.route(
+                    "/digests/{id}/run",
+                    web::post().to(routes::digests::trigger_digest),
+                );
+                r.route(
+                    "/digests/{id}/preview",
+                    web::post().to(routes::digests::preview_digest),
+                );
+                r.route("/preview", web::post().to(routes::digests::preview_topic));
+                r.route(
+                    "/providers/status",
+                    web::get().to(routes::providers::provider_status),
+                );
+                r.route("/jobs", web::get().to(routes::jobs::list_jobs));
+                r.route("/jobs/{id}", web::get().to(routes::jobs::get_job));
                 r.route(
                     "/notification",
                     web::post().to(routes::notification::send_notification),
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize)]
+pub struct AnthropicRequest {
+    pub model: String,
+    pub max_tokens: u32,
+    /// Anthropic takes the system prompt here rather than as a message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    pub messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<Tool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+}
+
+/// Structured output is done by forcing a call to a tool whose input is the schema.
+#[derive(Serialize)]
+pub struct Tool {
+    pub name: String,
+    pub description: String,
+    pub input_schema: Value,
+}
+
+#[derive(Serialize)]
+pub struct ToolChoice {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Message {
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Deserialize)]
+pub struct AnthropicResponse {
+    pub content: Vec<ContentBlock>,
+    pub stop_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ContentBlock {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub text: Option<String>,
+    /// Set on `tool_use` blocks.
+    pub input: Option<Value>,
+}
+
+/// Body of a non-2xx response.
+#[derive(Deserialize)]
+pub struct AnthropicErrorResponse {
+    pub error: AnthropicError,
+}
+
+#[derive(Deserialize)]
+pub struct AnthropicError {
+    /// e.g. `invalid_request_error`, `rate_limit_error` or `overloaded_error`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub message: String,
+}
@@ -42,6 +42,8 @@ pub struct WebPage {
     pub snippet: String,
     #[serde(rename = "dateLastCrawled")]
     pub date_last_crawled: String,
+    #[serde(rename = "datePublished")]
+    pub date_published: Option<String>,
     #[serde(rename = "primaryImageOfPage")]
     pub primary_image_of_page: Option<PrimaryImageOfPage>,
     #[serde(rename = "cachedPageUrl")]
@@ -132,8 +134,3 @@ pub struct RankingItem {
 pub struct RankingItemValue {
     pub id: String,
 }
-
-#[derive(Deserialize)]
-pub struct SearchQuery {
-    pub query: String,
-}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BraveSearchResponse {
+    /// Missing when Brave found no web results.
+    pub web: Option<BraveWebResults>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BraveWebResults {
+    pub results: Vec<BraveWebResult>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BraveWebResult {
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub description: String,
+    /// Human readable, e.g. `2 days ago`.
+    pub age: Option<String>,
+    /// ISO 8601 timestamp.
+    pub page_age: Option<String>,
+}
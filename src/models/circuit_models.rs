@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls go through.
+    Closed,
+    /// Calls fail immediately until the cooldown passes.
+    Open,
+    /// The cooldown passed and a single probe call decides whether to close again.
+    HalfOpen,
+}
+
+/// The circuit breaker of one upstream, as returned by `GET /api/providers/status`.
+#[derive(Debug, Serialize, Clone)]
+pub struct CircuitStatus {
+    pub upstream: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    pub opened_at: Option<DateTime<Utc>>,
+    /// When an open circuit lets a probe through.
+    pub retry_at: Option<DateTime<Utc>>,
+}
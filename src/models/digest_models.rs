@@ -0,0 +1,313 @@
+use crate::models::{
+    email_models::{FailedRecipient, Recipients},
+    job_models::QueryFailure,
+};
+use chrono::{DateTime, NaiveDateTime, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Digest {
+    pub id: String,
+    pub topic: String,
+    pub subject: String,
+    pub schedule: Schedule,
+    pub timezone: Tz,
+    pub send_to: Recipients,
+    #[serde(default)]
+    pub cc: Recipients,
+    #[serde(default)]
+    pub bcc: Recipients,
+    #[serde(default)]
+    pub reply_to: Option<String>,
+    /// Overrides the display name of the From address.
+    #[serde(default)]
+    pub from_name: Option<String>,
+    /// Sends each recipient their own copy instead of one email to everyone.
+    #[serde(default)]
+    pub send_individually: bool,
+    /// Generated files attached to the email.
+    #[serde(default)]
+    pub attachments: Vec<DigestAttachment>,
+    /// Registered search provider name, or the default search provider when unset.
+    #[serde(default)]
+    pub search_provider: Option<String>,
+    /// Registered LLM provider name, or the default LLM provider when unset.
+    #[serde(default)]
+    pub llm_provider: Option<String>,
+    /// Overrides the LLM provider's default model.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Most search queries the topic is split into, defaults to `DEFAULT_MAX_QUERIES`.
+    #[serde(default)]
+    pub max_queries: Option<usize>,
+    /// Whether to note failed searches at the end of the email, defaults to `true`.
+    #[serde(default)]
+    pub failure_footer: Option<bool>,
+    /// Providers tried in order when the ones above error, time out or return nothing.
+    #[serde(default)]
+    pub fallbacks: Vec<ProviderRoute>,
+    /// Occurrences before this are never caught up. Stamped on first load for digests saved
+    /// before this field existed.
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// When a digest is due, evaluated in the digest's timezone.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Schedule {
+    /// `sec min hour day-of-month month day-of-week [year]`; day-of-month and day-of-week
+    /// must both match, so `0 0 7 1-7 * Mon` is the first Monday of the month at 7am.
+    Cron {
+        expression: String,
+    },
+    Weekly {
+        days: Vec<Weekday>,
+        at: NaiveTime,
+    },
+    Monthly {
+        week: WeekOfMonth,
+        day: Weekday,
+        at: NaiveTime,
+    },
+    Interval {
+        every_minutes: u32,
+        starting: NaiveDateTime,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum WeekOfMonth {
+    First,
+    Second,
+    Third,
+    Fourth,
+    Last,
+}
+
+#[derive(Deserialize)]
+pub struct CreateDigestRequest {
+    pub topic: String,
+    pub subject: String,
+    pub schedule: Schedule,
+    pub timezone: Tz,
+    pub send_to: Recipients,
+    pub cc: Option<Recipients>,
+    pub bcc: Option<Recipients>,
+    pub reply_to: Option<String>,
+    pub from_name: Option<String>,
+    pub send_individually: Option<bool>,
+    pub attachments: Option<Vec<DigestAttachment>>,
+    pub search_provider: Option<String>,
+    pub llm_provider: Option<String>,
+    pub model: Option<String>,
+    pub max_queries: Option<usize>,
+    pub failure_footer: Option<bool>,
+    pub fallbacks: Option<Vec<ProviderRoute>>,
+}
+
+/// Body of `PUT /api/digests/{id}`. Fields left out are unchanged, and optional fields sent
+/// as `null` are cleared.
+#[derive(Deserialize)]
+pub struct UpdateDigestRequest {
+    pub topic: Option<String>,
+    pub subject: Option<String>,
+    pub schedule: Option<Schedule>,
+    pub timezone: Option<Tz>,
+    pub send_to: Option<Recipients>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub cc: Option<Option<Recipients>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub bcc: Option<Option<Recipients>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub reply_to: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub from_name: Option<Option<String>>,
+    pub send_individually: Option<bool>,
+    pub attachments: Option<Vec<DigestAttachment>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub search_provider: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub llm_provider: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub model: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub max_queries: Option<Option<usize>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub failure_footer: Option<Option<bool>>,
+    pub fallbacks: Option<Vec<ProviderRoute>>,
+}
+
+/// Tells a field sent as `null`, `Some(None)`, apart from one left out, `None`.
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// A file generated from the digest and attached to its email.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestAttachment {
+    /// The digest's markdown as `digest.md`.
+    Markdown,
+    /// The items in the digest as `items.csv`.
+    Csv,
+    /// An all-day event per item deadline as `deadlines.ics`, left out when no item has one.
+    Ics,
+}
+
+/// One step of a digest's provider fallback chain. Unset providers are the defaults, e.g.
+/// `{ "search_provider": "searxng", "llm_provider": "ollama", "model": "llama3.1" }`.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct ProviderRoute {
+    #[serde(default)]
+    pub search_provider: Option<String>,
+    #[serde(default)]
+    pub llm_provider: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+/// Providers that produced part of a digest, in order of first use.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct ProvidersUsed {
+    pub search: Vec<String>,
+    pub llm: Vec<String>,
+}
+
+/// A digest run that sent at least one email.
+#[derive(Debug, Clone, Default)]
+pub struct DigestSent {
+    pub providers: ProvidersUsed,
+    /// Recipients of an individually sent digest whose copy wasn't accepted.
+    pub failed_recipients: Vec<FailedRecipient>,
+}
+
+/// Scheduler bookkeeping for a digest, persisted so missed runs survive restarts.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct DigestRunState {
+    pub last_success_at: Option<DateTime<Utc>>,
+    /// The scheduled occurrence covered by the last successful run.
+    pub last_occurrence: Option<DateTime<Utc>>,
+    /// The last failed scheduled run, cleared along with `last_error` by a success.
+    pub last_failure_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    /// Recently sent scheduled occurrences, used to refuse sending the same one twice.
+    #[serde(default)]
+    pub sent_occurrences: Vec<DateTime<Utc>>,
+    /// Providers the last successful run ended up using after any fallbacks.
+    #[serde(default)]
+    pub last_providers: Option<ProvidersUsed>,
+    /// Recipients the last successful run couldn't reach. The occurrence still counts as
+    /// sent, so the others don't get it twice.
+    #[serde(default)]
+    pub last_failed_recipients: Vec<FailedRecipient>,
+    /// Runs in progress. Forced runs can hold a claim alongside another run.
+    #[serde(default)]
+    pub claims: Vec<RunClaim>,
+}
+
+/// A run of a digest in progress, released by its token when the run finishes.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RunClaim {
+    pub token: String,
+    pub occurrence: Option<DateTime<Utc>>,
+    pub claimed_at: DateTime<Utc>,
+}
+
+/// Provider and model overrides for generating a digest.
+#[derive(Deserialize, Default, Clone)]
+pub struct GenerateOptions {
+    /// Overrides the digest's search provider.
+    pub search_provider: Option<String>,
+    /// Overrides the digest's LLM provider.
+    pub llm_provider: Option<String>,
+    /// Model used for query optimisation and transforms.
+    pub model: Option<String>,
+    /// Overrides the digest's query cap.
+    pub max_queries: Option<usize>,
+    /// Overrides the digest's failure footer setting.
+    pub failure_footer: Option<bool>,
+    /// Overrides the digest's fallback chain, `[]` disables fallback.
+    pub fallbacks: Option<Vec<ProviderRoute>>,
+}
+
+/// Overrides for a single on-demand run of a digest.
+#[derive(Deserialize, Default, Clone)]
+pub struct RunOptions {
+    /// Sends to these recipients instead, without the digest's `cc` and `bcc`.
+    pub send_to: Option<Recipients>,
+    #[serde(flatten)]
+    pub generate: GenerateOptions,
+    /// Run even if the digest is already running.
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// An ad-hoc preview of a topic that isn't stored as a digest.
+#[derive(Deserialize)]
+pub struct PreviewRequest {
+    pub topic: String,
+    #[serde(flatten)]
+    pub options: GenerateOptions,
+}
+
+/// Everything the pipeline produced for a digest, up to but not including the email.
+#[derive(Debug, Serialize, Clone)]
+pub struct DigestArtifacts {
+    pub queries: Vec<OptimisedQuery>,
+    pub results: Vec<QueryResult>,
+    /// Queries left out because their search or transform failed.
+    pub failures: Vec<QueryFailure>,
+    pub providers: ProvidersUsed,
+    pub markdown: String,
+    pub html: String,
+    /// Plain-text rendering of `markdown`, sent alongside the HTML.
+    pub text: String,
+}
+
+/// The structured reply expected when extracting items for attachments.
+#[derive(Debug, Deserialize)]
+pub struct DigestItems {
+    pub items: Vec<DigestItem>,
+}
+
+/// One entry of a digest, e.g. a grant or an article.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DigestItem {
+    pub title: String,
+    pub summary: String,
+    #[serde(default)]
+    pub link: Option<String>,
+    /// `YYYY-MM-DD`, when the item has a deadline or closing date.
+    #[serde(default)]
+    pub deadline: Option<String>,
+}
+
+/// The structured reply expected from query optimisation.
+#[derive(Debug, Deserialize)]
+pub struct OptimisedQueries {
+    pub queries: Vec<OptimisedQuery>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OptimisedQuery {
+    pub query: String,
+    /// Why the model chose this query, `None` when falling back to the raw topic.
+    #[serde(default)]
+    pub rationale: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct QueryResult {
+    pub query: String,
+    pub content: String,
+    pub search_provider: String,
+    /// `None` when the search provider summarised the results itself.
+    pub llm_provider: Option<String>,
+}
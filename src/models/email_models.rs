@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+/// Body of `POST /api/email`.
+#[derive(Deserialize)]
+pub struct Email {
+    pub email: Recipients,
+    #[serde(default)]
+    pub cc: Recipients,
+    #[serde(default)]
+    pub bcc: Recipients,
+    #[serde(default)]
+    pub reply_to: Option<String>,
+    /// Overrides the display name of the From address.
+    #[serde(default)]
+    pub from_name: Option<String>,
+    /// Sends a separate copy to each address in `email`, `cc` and `bcc`, so no recipient sees
+    /// the others.
+    #[serde(default)]
+    pub send_individually: bool,
+    pub subject: String,
+    /// HTML body.
+    pub body: String,
+    /// Plain-text alternative, sent as the first part so text-only clients show it.
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+}
+
+/// A file attached to an email.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Attachment {
+    pub filename: String,
+    /// MIME type, e.g. `text/csv`.
+    pub content_type: String,
+    /// Base64 encoded file contents.
+    pub content: String,
+}
+
+/// A list of addresses, each either `bot@example.com` or `Name <bot@example.com>`. A single
+/// address can be given as a plain string.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+#[serde(from = "OneOrMany")]
+pub struct Recipients(pub Vec<String>);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<OneOrMany> for Recipients {
+    fn from(value: OneOrMany) -> Self {
+        match value {
+            OneOrMany::One(address) => Recipients(vec![address]),
+            OneOrMany::Many(addresses) => Recipients(addresses),
+        }
+    }
+}
+
+/// Response of `POST /api/email`.
+#[derive(Serialize)]
+pub struct EmailSent {
+    /// The `Message-ID` header of each sent email, one per recipient when sent individually.
+    pub message_ids: Vec<String>,
+    /// Recipients whose individual copy wasn't accepted while the others were.
+    pub failed: Vec<FailedRecipient>,
+}
+
+/// A recipient the SMTP server didn't accept, with its reply.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct FailedRecipient {
+    pub recipient: String,
+    pub error: String,
+}
@@ -0,0 +1,102 @@
+use crate::{
+    models::{digest_models::ProvidersUsed, email_models::FailedRecipient},
+    services::error::PipelineError,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct TriggerQuery {
+    /// Send even if the digest is already running or its occurrence was already sent.
+    pub force: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Job {
+    pub id: String,
+    pub trigger: JobTrigger,
+    pub status: JobStatus,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+    pub digests: Vec<DigestProgress>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobTrigger {
+    Api,
+    Scheduler,
+    /// A single digest run on demand via `/api/digests/{id}/run`.
+    Manual,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    PartiallyFailed,
+    Failed,
+    /// Every digest was refused by the run-once guard.
+    Skipped,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DigestProgress {
+    pub digest_id: String,
+    pub subject: String,
+    pub scheduled_for: Option<DateTime<Utc>>,
+    pub stage: DigestStage,
+    pub queries_generated: usize,
+    pub searches_completed: usize,
+    pub searches_failed: usize,
+    pub transformed: bool,
+    pub emailed: bool,
+    /// `Message-ID` of each sent email, one per recipient when sent individually.
+    pub message_ids: Vec<String>,
+    /// Recipients whose individually sent copy wasn't accepted.
+    pub failed_recipients: Vec<FailedRecipient>,
+    pub error: Option<String>,
+    /// Sub-queries that failed while the rest of the digest carried on.
+    pub failures: Vec<QueryFailure>,
+    /// Providers used so far, including any fallbacks.
+    pub providers: ProvidersUsed,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+    pub timings: StageTimings,
+}
+
+/// A search query that was left out of a digest, e.g.
+/// `{ "query": "...", "kind": "search", "provider": "brave", "message": "..." }`.
+#[derive(Debug, Serialize, Clone)]
+pub struct QueryFailure {
+    pub query: String,
+    #[serde(flatten)]
+    pub error: PipelineError,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestStage {
+    Pending,
+    GeneratingQueries,
+    Searching,
+    Transforming,
+    Emailing,
+    Emailed,
+    Failed,
+    Skipped,
+}
+
+/// Milliseconds spent in each pipeline stage.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct StageTimings {
+    pub queries_ms: Option<u128>,
+    pub search_ms: Option<u128>,
+    pub transform_ms: Option<u128>,
+    pub email_ms: Option<u128>,
+}
@@ -0,0 +1,20 @@
+use serde_json::Value;
+
+/// A single completion for an `LlmProvider`.
+#[derive(Debug, Clone, Default)]
+pub struct LlmRequest {
+    /// Overrides the provider's default model.
+    pub model: Option<String>,
+    pub system: Option<String>,
+    pub prompt: String,
+    /// Asks for a JSON reply matching this schema, returned as the reply text.
+    pub response_schema: Option<ResponseSchema>,
+    /// Skips reading the response cache. The fresh reply is still cached.
+    pub no_cache: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResponseSchema {
+    pub name: String,
+    pub schema: Value,
+}
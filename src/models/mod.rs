@@ -1,3 +1,12 @@
+pub mod anthropic_models;
 pub mod bing_models;
+pub mod brave_models;
+pub mod circuit_models;
+pub mod digest_models;
+pub mod email_models;
+pub mod job_models;
+pub mod llm_models;
 pub mod open_ai_models;
 pub mod perplexity_models;
+pub mod search_models;
+pub mod searxng_models;
@@ -1,9 +1,27 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 #[derive(Serialize, Deserialize)]
 pub struct OpenAiRequest {
     pub model: String,
     pub messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+}
+
+/// Structured output, `{ "type": "json_schema", "json_schema": { ... } }`.
+#[derive(Serialize, Deserialize)]
+pub struct ResponseFormat {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub json_schema: JsonSchema,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JsonSchema {
+    pub name: String,
+    pub schema: Value,
+    pub strict: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -22,8 +40,15 @@ pub struct Choice {
     pub message: Option<Message>,
 }
 
+/// Body of `POST /api/transform`.
 #[derive(Deserialize, Debug)]
 pub struct CompletionRequest {
-    pub model: String,
+    /// Registered LLM provider name, defaults to `LLM_PROVIDER`.
+    pub provider: Option<String>,
+    /// Defaults to the provider's model.
+    pub model: Option<String>,
+    pub system: Option<String>,
     pub query: String,
+    /// Skips the response cache for this call.
+    pub no_cache: Option<bool>,
 }
@@ -4,12 +4,18 @@ use serde::{Deserialize, Serialize};
 pub struct SearchRequest {
     pub query: String,
     pub use_sonar_small: Option<bool>,
+    /// Overrides the sonar model picked by `use_sonar_small`.
+    pub model: Option<String>,
+    /// Skips the response cache for this call.
+    pub no_cache: Option<bool>,
 }
 
 #[derive(Serialize)]
 pub struct PerplexityRequest {
     pub model: String,
     pub messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_recency_filter: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -21,6 +27,8 @@ pub struct Message {
 #[derive(Deserialize)]
 pub struct PerplexityResponse {
     pub choices: Vec<Choice>,
+    #[serde(default)]
+    pub citations: Vec<String>,
 }
 
 #[derive(Deserialize)]
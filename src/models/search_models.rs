@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Freshness {
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct WebSearchRequest {
+    pub query: String,
+    pub count: Option<u32>,
+    pub freshness: Option<Freshness>,
+    /// Market/locale code such as `en-AU`.
+    pub market: Option<String>,
+    /// Skips reading the response cache. The fresh response is still cached.
+    #[serde(default, skip_serializing)]
+    pub no_cache: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WebSearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+    pub published: Option<String>,
+    /// Providers that returned this result, set by meta-search.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct WebSearchResponse {
+    pub results: Vec<WebSearchResult>,
+    /// A ready-made markdown answer from providers that summarise their own results, like
+    /// Perplexity. When set, the pipeline uses it instead of transforming `results`.
+    pub answer: Option<String>,
+}
+
+/// Body of `POST /api/search`.
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    /// Registered search provider name, defaults to `SEARCH_PROVIDER`.
+    pub provider: Option<String>,
+    #[serde(flatten)]
+    pub request: WebSearchRequest,
+}
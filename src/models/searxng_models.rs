@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+/// A SearxNG `/search?format=json` response.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SearxngSearchResponse {
+    pub results: Vec<SearxngResult>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SearxngResult {
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub content: String,
+    #[serde(rename = "publishedDate")]
+    pub published_date: Option<String>,
+}
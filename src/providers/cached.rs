@@ -0,0 +1,102 @@
+use crate::{
+    constants::utility::log_query,
+    models::{
+        llm_models::LlmRequest,
+        search_models::{WebSearchRequest, WebSearchResponse},
+    },
+    providers::{llm::LlmProvider, search::SearchProvider},
+    storage::response_cache::{CallType, ResponseCache},
+};
+use futures_util::future::BoxFuture;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Serves repeated searches from the response cache. Empty responses aren't cached.
+pub struct CachedSearch {
+    pub name: String,
+    pub provider: Arc<dyn SearchProvider>,
+    pub cache: Arc<ResponseCache>,
+}
+
+impl SearchProvider for CachedSearch {
+    fn search<'a>(
+        &'a self,
+        request: &'a WebSearchRequest,
+    ) -> BoxFuture<'a, Result<WebSearchResponse, String>> {
+        Box::pin(async move {
+            let model = self.provider.model();
+            let key = ResponseCache::key(&self.name, None, model, &json!(request));
+            if !request.no_cache {
+                if let Some(response) = self.cache.get(CallType::Search, &key) {
+                    log_query(&format!("Cached {} search: {}", self.name, request.query));
+                    return Ok(response);
+                }
+            }
+            let response = self.provider.search(request).await?;
+            if response.answer.is_some() || !response.results.is_empty() {
+                self.cache
+                    .put(CallType::Search, &key, &self.name, model, &response);
+            }
+            Ok(response)
+        })
+    }
+
+    fn model(&self) -> Option<&str> {
+        self.provider.model()
+    }
+
+    fn is_available(&self) -> bool {
+        self.provider.is_available()
+    }
+}
+
+/// Serves repeated completions from the response cache. Empty replies aren't cached.
+pub struct CachedLlm {
+    pub name: String,
+    pub provider: Arc<dyn LlmProvider>,
+    pub cache: Arc<ResponseCache>,
+}
+
+impl LlmProvider for CachedLlm {
+    fn complete<'a>(&'a self, request: &'a LlmRequest) -> BoxFuture<'a, Result<String, String>> {
+        Box::pin(async move {
+            let model = request
+                .model
+                .as_deref()
+                .unwrap_or(self.provider.default_model());
+            let body = json!({
+                "system": request.system,
+                "prompt": request.prompt,
+                "response_schema": request
+                    .response_schema
+                    .as_ref()
+                    .map(|response_schema| json!([response_schema.name, response_schema.schema])),
+            });
+            let key = ResponseCache::key(&self.name, self.base_url(), Some(model), &body);
+            if !request.no_cache {
+                if let Some(reply) = self.cache.get(CallType::Llm, &key) {
+                    log_query(&format!("Cached {} completion with {}", self.name, model));
+                    return Ok(reply);
+                }
+            }
+            let reply = self.provider.complete(request).await?;
+            if !reply.trim().is_empty() {
+                self.cache
+                    .put(CallType::Llm, &key, &self.name, Some(model), &reply);
+            }
+            Ok(reply)
+        })
+    }
+
+    fn default_model(&self) -> &str {
+        self.provider.default_model()
+    }
+
+    fn base_url(&self) -> Option<&str> {
+        self.provider.base_url()
+    }
+
+    fn is_available(&self) -> bool {
+        self.provider.is_available()
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{
+    constants::utility::{data_path, log_error, log_query},
+    storage::{read_json, write_json},
+};
+use reqwest::{header::CONTENT_TYPE, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha1::{Digest, Sha1};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CassetteMode {
+    /// Calls upstreams as usual and saves every request/response pair.
+    Record,
+    /// Serves saved responses and never calls upstreams. Unrecorded requests fail.
+    Replay,
+}
+
+/// Outbound calls saved as fixture files, one per distinct request, under
+/// `<dir>/<upstream>/<hash of method, url and body>.json`.
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    pub mode: CassetteMode,
+    dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    body: Option<RecordedBody>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    content_type: Option<String>,
+    body: RecordedBody,
+}
+
+/// JSON is stored as-is so fixtures stay readable and editable.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RecordedBody {
+    Json(Value),
+    Text(String),
+}
+
+impl RecordedBody {
+    fn new(bytes: &[u8]) -> Self {
+        match serde_json::from_slice(bytes) {
+            Ok(value) => RecordedBody::Json(value),
+            Err(_) => RecordedBody::Text(String::from_utf8_lossy(bytes).into_owned()),
+        }
+    }
+
+    fn into_string(self) -> String {
+        match self {
+            RecordedBody::Json(value) => value.to_string(),
+            RecordedBody::Text(text) => text,
+        }
+    }
+}
+
+impl Cassette {
+    pub fn new(mode: CassetteMode, dir: PathBuf) -> Self {
+        Cassette { mode, dir }
+    }
+
+    /// `CASSETTE_MODE` is `record` or `replay`, unset or `off` disables cassettes. Fixtures live
+    /// in `CASSETTE_DIR`, defaulting to `$DATA_DIR/cassettes`.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let mode = match std::env::var("CASSETTE_MODE").unwrap_or_default().as_str() {
+            "" | "off" => return Ok(None),
+            "record" => CassetteMode::Record,
+            "replay" => CassetteMode::Replay,
+            mode => {
+                return Err(format!(
+                    "CASSETTE_MODE must be record, replay or off, got '{}'",
+                    mode
+                ))
+            }
+        };
+        let dir = std::env::var("CASSETTE_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| data_path("cassettes"));
+        Ok(Some(Cassette::new(mode, dir)))
+    }
+
+    /// Serves the recorded response to the request built by `request`.
+    pub fn replay(&self, upstream: &str, request: RequestBuilder) -> Result<Response, String> {
+        let (recorded, path) = self.request(upstream, request)?;
+        let interaction = read_json::<Interaction>(&path)
+            .map_err(|e| format!("Invalid cassette {:?}: {}", path, e))?
+            .ok_or_else(|| {
+                format!(
+                    "No recorded response for {} {} in {:?}",
+                    recorded.method, recorded.url, path
+                )
+            })?;
+        log_query(&format!("Replayed {} {}", recorded.method, recorded.url));
+        to_response(interaction.response)
+    }
+
+    /// Saves `response` as the reply to the request built by `request`, returning an
+    /// equivalent response since reading the body consumes it.
+    pub async fn record(
+        &self,
+        upstream: &str,
+        request: RequestBuilder,
+        response: Response,
+    ) -> Result<Response, String> {
+        let status = response.status().as_u16();
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+        let recorded = RecordedResponse {
+            status,
+            content_type,
+            body: RecordedBody::new(&bytes),
+        };
+        let (request, path) = self.request(upstream, request)?;
+        let interaction = Interaction {
+            request,
+            response: recorded,
+        };
+        if let Err(e) = write_json(&path, &interaction) {
+            log_error(&format!("Failed to record {:?}: {}", path, e));
+        }
+        to_response(interaction.response)
+    }
+
+    /// Saves an email that was, or in replay mode would have been, sent.
+    pub fn record_email(&self, email: &Value) {
+        let path = self
+            .dir
+            .join("smtp")
+            .join(format!("{}.json", hash(&email.to_string())));
+        if let Err(e) = write_json(&path, &email) {
+            log_error(&format!("Failed to record email {:?}: {}", path, e));
+        }
+    }
+
+    fn request(
+        &self,
+        upstream: &str,
+        request: RequestBuilder,
+    ) -> Result<(RecordedRequest, PathBuf), String> {
+        let request = request.build().map_err(|e| e.to_string())?;
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(RecordedBody::new);
+        let recorded = RecordedRequest {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            body,
+        };
+        let key = hash(&json!([recorded.method, recorded.url, recorded.body]).to_string());
+        let path = self.dir.join(upstream).join(format!("{}.json", key));
+        Ok((recorded, path))
+    }
+}
+
+fn hash(value: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(value);
+    format!("{:x}", hasher.finalize())
+}
+
+fn to_response(recorded: RecordedResponse) -> Result<Response, String> {
+    let mut response = http::Response::builder().status(recorded.status);
+    if let Some(content_type) = recorded.content_type {
+        response = response.header(CONTENT_TYPE, content_type);
+    }
+    response
+        .body(recorded.body.into_string())
+        .map(Response::from)
+        .map_err(|e| format!("Invalid recorded response: {}", e))
+}
@@ -0,0 +1,300 @@
+use crate::{
+    constants::utility::log_error,
+    models::circuit_models::{CircuitState, CircuitStatus},
+};
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Mutex;
+
+/// Stops calling an upstream after `threshold` consecutive failures. Calls fail immediately for
+/// `cooldown`, then one probe is let through: success closes the circuit, failure reopens it.
+/// A `threshold` of 0 disables the breaker.
+pub struct CircuitBreaker {
+    upstream: String,
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<Breaker>,
+    clock: Box<dyn Fn() -> DateTime<Utc> + Send + Sync>,
+}
+
+struct Breaker {
+    state: CircuitState,
+    consecutive_failures: u32,
+    last_error: Option<String>,
+    opened_at: Option<DateTime<Utc>>,
+    /// When the half-open probe was let through. A probe that never reports back (e.g. a
+    /// cancelled request) is given up on after another cooldown.
+    probe_started_at: Option<DateTime<Utc>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(upstream: &str, threshold: u32, cooldown: std::time::Duration) -> Self {
+        CircuitBreaker::with_clock(upstream, threshold, cooldown, Box::new(Utc::now))
+    }
+
+    /// A breaker that reads the time from `clock` instead of the system clock.
+    pub fn with_clock(
+        upstream: &str,
+        threshold: u32,
+        cooldown: std::time::Duration,
+        clock: Box<dyn Fn() -> DateTime<Utc> + Send + Sync>,
+    ) -> Self {
+        CircuitBreaker {
+            upstream: upstream.to_string(),
+            threshold,
+            cooldown: Duration::milliseconds(cooldown.as_millis() as i64),
+            state: Mutex::new(Breaker {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                last_error: None,
+                opened_at: None,
+                probe_started_at: None,
+            }),
+            clock,
+        }
+    }
+
+    /// Whether a call would be let through now, without claiming the half-open probe.
+    pub fn is_available(&self) -> bool {
+        let breaker = self.state.lock().unwrap();
+        self.refusal(&breaker, (self.clock)()).is_none()
+    }
+
+    /// Claims a call, moving an open circuit whose cooldown has passed to half-open.
+    pub fn acquire(&self) -> Result<(), String> {
+        let now = (self.clock)();
+        let mut breaker = self.state.lock().unwrap();
+        if let Some(reason) = self.refusal(&breaker, now) {
+            return Err(reason);
+        }
+        if breaker.state != CircuitState::Closed {
+            breaker.state = CircuitState::HalfOpen;
+            breaker.probe_started_at = Some(now);
+        }
+        Ok(())
+    }
+
+    pub fn record_success(&self) {
+        let mut breaker = self.state.lock().unwrap();
+        if breaker.state != CircuitState::Closed {
+            log_error(&format!("Circuit for {} closed", self.upstream));
+        }
+        breaker.state = CircuitState::Closed;
+        breaker.consecutive_failures = 0;
+        breaker.opened_at = None;
+        breaker.probe_started_at = None;
+    }
+
+    pub fn record_failure(&self, error: &str) {
+        if self.threshold == 0 {
+            return;
+        }
+        let mut breaker = self.state.lock().unwrap();
+        breaker.consecutive_failures += 1;
+        breaker.last_error = Some(error.to_string());
+        let trips = match breaker.state {
+            CircuitState::Closed => breaker.consecutive_failures >= self.threshold,
+            CircuitState::HalfOpen => true,
+            CircuitState::Open => false,
+        };
+        if trips {
+            log_error(&format!(
+                "Circuit for {} opened after {} failures: {}",
+                self.upstream, breaker.consecutive_failures, error
+            ));
+            breaker.state = CircuitState::Open;
+            breaker.opened_at = Some((self.clock)());
+            breaker.probe_started_at = None;
+        }
+    }
+
+    pub fn status(&self) -> CircuitStatus {
+        let breaker = self.state.lock().unwrap();
+        CircuitStatus {
+            upstream: self.upstream.clone(),
+            state: breaker.state,
+            consecutive_failures: breaker.consecutive_failures,
+            last_error: breaker.last_error.clone(),
+            opened_at: breaker.opened_at,
+            retry_at: match breaker.state {
+                CircuitState::Open => breaker.opened_at.map(|opened_at| opened_at + self.cooldown),
+                _ => None,
+            },
+        }
+    }
+
+    /// Why a call made at `now` would be refused, if it would be.
+    fn refusal(&self, breaker: &Breaker, now: DateTime<Utc>) -> Option<String> {
+        let cooled_down =
+            |since: Option<DateTime<Utc>>| since.is_none_or(|since| now >= since + self.cooldown);
+        match breaker.state {
+            CircuitState::Closed => None,
+            CircuitState::Open if cooled_down(breaker.opened_at) => None,
+            CircuitState::HalfOpen if cooled_down(breaker.probe_started_at) => None,
+            CircuitState::Open => Some(format!(
+                "Circuit for {} is open after {} failures, last: {}",
+                self.upstream,
+                breaker.consecutive_failures,
+                breaker.last_error.as_deref().unwrap_or_default()
+            )),
+            CircuitState::HalfOpen => Some(format!(
+                "Circuit for {} is half-open and waiting on a probe",
+                self.upstream
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::sync::Arc;
+
+    /// A clock that only moves when the test advances it.
+    #[derive(Clone)]
+    struct TestClock(Arc<Mutex<DateTime<Utc>>>);
+
+    impl TestClock {
+        fn new() -> Self {
+            TestClock(Arc::new(Mutex::new(
+                Utc.with_ymd_and_hms(2026, 10, 18, 7, 0, 0).unwrap(),
+            )))
+        }
+
+        fn now(&self) -> DateTime<Utc> {
+            *self.0.lock().unwrap()
+        }
+
+        fn advance(&self, seconds: i64) {
+            *self.0.lock().unwrap() += Duration::seconds(seconds);
+        }
+    }
+
+    /// Opens after 2 failures and cools down for a minute.
+    fn breaker(clock: &TestClock) -> CircuitBreaker {
+        let clock = clock.clone();
+        CircuitBreaker::with_clock(
+            "test",
+            2,
+            std::time::Duration::from_secs(60),
+            Box::new(move || clock.now()),
+        )
+    }
+
+    fn trip(breaker: &CircuitBreaker) {
+        for _ in 0..2 {
+            breaker.acquire().unwrap();
+            breaker.record_failure("503 Service Unavailable");
+        }
+    }
+
+    #[test]
+    fn opens_after_consecutive_failures() {
+        let clock = TestClock::new();
+        let breaker = breaker(&clock);
+        breaker.acquire().unwrap();
+        breaker.record_failure("timed out");
+        breaker.record_success();
+        breaker.acquire().unwrap();
+        breaker.record_failure("timed out");
+        assert_eq!(breaker.status().state, CircuitState::Closed);
+
+        breaker.acquire().unwrap();
+        breaker.record_failure("503 Service Unavailable");
+        let status = breaker.status();
+        assert_eq!(status.state, CircuitState::Open);
+        assert_eq!(status.consecutive_failures, 2);
+        assert_eq!(status.opened_at, Some(clock.now()));
+        assert_eq!(status.retry_at, Some(clock.now() + Duration::seconds(60)));
+    }
+
+    #[test]
+    fn refuses_calls_until_the_cooldown_passes() {
+        let clock = TestClock::new();
+        let breaker = breaker(&clock);
+        trip(&breaker);
+        assert!(!breaker.is_available());
+        let refusal = breaker.acquire().unwrap_err();
+        assert!(refusal.contains("503 Service Unavailable"), "{}", refusal);
+
+        clock.advance(59);
+        assert!(breaker.acquire().is_err());
+        clock.advance(1);
+        assert!(breaker.is_available());
+        assert_eq!(breaker.status().state, CircuitState::Open);
+    }
+
+    #[test]
+    fn lets_one_probe_through_when_half_open() {
+        let clock = TestClock::new();
+        let breaker = breaker(&clock);
+        trip(&breaker);
+        clock.advance(60);
+        breaker.acquire().unwrap();
+        assert_eq!(breaker.status().state, CircuitState::HalfOpen);
+        assert!(!breaker.is_available());
+        assert!(breaker.acquire().is_err());
+    }
+
+    #[test]
+    fn closes_when_the_probe_succeeds() {
+        let clock = TestClock::new();
+        let breaker = breaker(&clock);
+        trip(&breaker);
+        clock.advance(60);
+        breaker.acquire().unwrap();
+        breaker.record_success();
+        let status = breaker.status();
+        assert_eq!(status.state, CircuitState::Closed);
+        assert_eq!(status.consecutive_failures, 0);
+        assert_eq!(status.retry_at, None);
+        assert!(breaker.acquire().is_ok());
+    }
+
+    #[test]
+    fn reopens_when_the_probe_fails() {
+        let clock = TestClock::new();
+        let breaker = breaker(&clock);
+        trip(&breaker);
+        clock.advance(60);
+        breaker.acquire().unwrap();
+        clock.advance(5);
+        breaker.record_failure("timed out");
+        let status = breaker.status();
+        assert_eq!(status.state, CircuitState::Open);
+        assert_eq!(status.opened_at, Some(clock.now()));
+        assert!(breaker.acquire().is_err());
+        clock.advance(60);
+        assert!(breaker.acquire().is_ok());
+    }
+
+    #[test]
+    fn gives_up_on_a_probe_that_never_reports_back() {
+        let clock = TestClock::new();
+        let breaker = breaker(&clock);
+        trip(&breaker);
+        clock.advance(60);
+        breaker.acquire().unwrap();
+        clock.advance(59);
+        assert!(breaker.acquire().is_err());
+        clock.advance(1);
+        assert!(breaker.acquire().is_ok());
+        assert_eq!(breaker.status().state, CircuitState::HalfOpen);
+    }
+
+    #[test]
+    fn never_opens_with_a_zero_threshold() {
+        let clock = TestClock::new();
+        let breaker = CircuitBreaker::with_clock(
+            "test",
+            0,
+            std::time::Duration::from_secs(60),
+            Box::new(move || clock.now()),
+        );
+        for _ in 0..10 {
+            breaker.acquire().unwrap();
+            breaker.record_failure("timed out");
+        }
+        assert_eq!(breaker.status().state, CircuitState::Closed);
+    }
+}
@@ -0,0 +1,334 @@
+use crate::{
+    constants::utility::log_error,
+    models::circuit_models::CircuitStatus,
+    providers::{
+        cassette::{Cassette, CassetteMode},
+        circuit::CircuitBreaker,
+    },
+};
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::{header::RETRY_AFTER, Client, RequestBuilder, Response, StatusCode};
+use std::{fmt, sync::Arc, time::Duration};
+
+pub const DEFAULT_CONNECT_TIMEOUT_MS: u64 = 10_000;
+pub const DEFAULT_READ_TIMEOUT_MS: u64 = 120_000;
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+pub const DEFAULT_RETRY_BASE_MS: u64 = 500;
+pub const DEFAULT_RETRY_MAX_MS: u64 = 30_000;
+pub const DEFAULT_BREAKER_THRESHOLD: u32 = 5;
+pub const DEFAULT_BREAKER_COOLDOWN_MS: u64 = 60_000;
+
+/// Timeouts and retry policy for one upstream.
+#[derive(Debug, Clone)]
+pub struct HttpSettings {
+    pub connect_timeout: Duration,
+    /// Longest wait between reads of the response, so slow but streaming LLM replies survive.
+    pub read_timeout: Duration,
+    /// Retries after the first attempt.
+    pub max_retries: u32,
+    pub retry_base: Duration,
+    /// Upper bound on a single backoff. A `Retry-After` longer than this isn't waited out.
+    pub retry_max: Duration,
+    /// Consecutive failed calls that open the circuit, 0 disables the breaker.
+    pub breaker_threshold: u32,
+    pub breaker_cooldown: Duration,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        HttpSettings {
+            connect_timeout: Duration::from_millis(DEFAULT_CONNECT_TIMEOUT_MS),
+            read_timeout: Duration::from_millis(DEFAULT_READ_TIMEOUT_MS),
+            max_retries: DEFAULT_MAX_RETRIES,
+            retry_base: Duration::from_millis(DEFAULT_RETRY_BASE_MS),
+            retry_max: Duration::from_millis(DEFAULT_RETRY_MAX_MS),
+            breaker_threshold: DEFAULT_BREAKER_THRESHOLD,
+            breaker_cooldown: Duration::from_millis(DEFAULT_BREAKER_COOLDOWN_MS),
+        }
+    }
+}
+
+impl HttpSettings {
+    /// Reads `HTTP_<NAME>_CONNECT_TIMEOUT_MS`, `HTTP_<NAME>_READ_TIMEOUT_MS`,
+    /// `HTTP_<NAME>_MAX_RETRIES`, `HTTP_<NAME>_RETRY_BASE_MS`, `HTTP_<NAME>_RETRY_MAX_MS`,
+    /// `HTTP_<NAME>_BREAKER_THRESHOLD` and `HTTP_<NAME>_BREAKER_COOLDOWN_MS`, falling back to the same variables without the provider name, then the defaults.
+    pub fn from_env(name: &str) -> Result<Self, String> {
+        let defaults = HttpSettings::default();
+        Ok(HttpSettings {
+            connect_timeout: millis(name, "CONNECT_TIMEOUT_MS", defaults.connect_timeout)?,
+            read_timeout: millis(name, "READ_TIMEOUT_MS", defaults.read_timeout)?,
+            max_retries: number(name, "MAX_RETRIES")?.unwrap_or(defaults.max_retries),
+            retry_base: millis(name, "RETRY_BASE_MS", defaults.retry_base)?,
+            retry_max: millis(name, "RETRY_MAX_MS", defaults.retry_max)?,
+            breaker_threshold: number(name, "BREAKER_THRESHOLD")?
+                .unwrap_or(defaults.breaker_threshold),
+            breaker_cooldown: millis(name, "BREAKER_COOLDOWN_MS", defaults.breaker_cooldown)?,
+        })
+    }
+}
+
+/// Why a request to an upstream didn't get a response.
+#[derive(Debug)]
+pub enum HttpError {
+    /// The upstream's circuit breaker refused the call without sending it.
+    CircuitOpen(String),
+    /// Recording or replaying the call failed, e.g. nothing was recorded for it.
+    Cassette(String),
+    Request(reqwest::Error),
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::CircuitOpen(message) | HttpError::Cassette(message) => {
+                write!(f, "{}", message)
+            }
+            HttpError::Request(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// A pooled client for one upstream that retries throttled and failed requests and stops
+/// calling the upstream while its circuit breaker is open.
+#[derive(Clone)]
+pub struct HttpClient {
+    name: String,
+    client: Client,
+    settings: HttpSettings,
+    breaker: Arc<CircuitBreaker>,
+    cassette: Option<Cassette>,
+}
+
+impl HttpClient {
+    pub fn new(
+        name: &str,
+        settings: HttpSettings,
+        cassette: Option<Cassette>,
+    ) -> Result<Self, String> {
+        let client = Client::builder()
+            .connect_timeout(settings.connect_timeout)
+            .read_timeout(settings.read_timeout)
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client for {}: {}", name, e))?;
+        Ok(HttpClient {
+            name: name.to_string(),
+            client,
+            breaker: Arc::new(CircuitBreaker::new(
+                name,
+                settings.breaker_threshold,
+                settings.breaker_cooldown,
+            )),
+            settings,
+            cassette,
+        })
+    }
+
+    pub fn from_env(name: &str) -> Result<Self, String> {
+        HttpClient::new(name, HttpSettings::from_env(name)?, Cassette::from_env()?)
+    }
+
+    /// Whether the circuit breaker would let a request through now.
+    pub fn is_available(&self) -> bool {
+        self.breaker.is_available()
+    }
+
+    pub fn circuit(&self) -> CircuitStatus {
+        self.breaker.status()
+    }
+
+    /// Sends the request built by `build`, retrying connection failures, timeouts and
+    /// retryable statuses with jittered exponential backoff. A `Retry-After` header is used
+    /// as the delay instead. Once retries run out the last response or error is returned,
+    /// and counted against the circuit breaker.
+    ///
+    /// In cassette replay mode the recorded response is returned without any network call.
+    pub async fn send(
+        &self,
+        build: impl Fn(&Client) -> RequestBuilder,
+    ) -> Result<Response, HttpError> {
+        if let Some(cassette) = &self.cassette {
+            if cassette.mode == CassetteMode::Replay {
+                return cassette
+                    .replay(&self.name, build(&self.client))
+                    .map_err(HttpError::Cassette);
+            }
+        }
+        self.breaker.acquire().map_err(HttpError::CircuitOpen)?;
+        let result = self.send_with_retry(&build).await;
+        match &result {
+            Ok(response) if is_retryable(response.status()) => {
+                self.breaker.record_failure(&response.status().to_string())
+            }
+            Ok(_) => self.breaker.record_success(),
+            Err(e) => self.breaker.record_failure(&e.to_string()),
+        }
+        let response = result.map_err(HttpError::Request)?;
+        match &self.cassette {
+            Some(cassette) => cassette
+                .record(&self.name, build(&self.client), response)
+                .await
+                .map_err(HttpError::Cassette),
+            None => Ok(response),
+        }
+    }
+
+    async fn send_with_retry(
+        &self,
+        build: impl Fn(&Client) -> RequestBuilder,
+    ) -> Result<Response, reqwest::Error> {
+        let mut attempt = 0;
+        loop {
+            let result = build(&self.client).send().await;
+            if attempt >= self.settings.max_retries {
+                return result;
+            }
+            let delay = match &result {
+                Ok(response) if is_retryable(response.status()) => match retry_after(response) {
+                    Some(delay) if delay > self.settings.retry_max => return result,
+                    Some(delay) => delay,
+                    None => self.backoff(attempt),
+                },
+                Err(e) if e.is_connect() || e.is_timeout() => self.backoff(attempt),
+                _ => return result,
+            };
+            let reason = match &result {
+                Ok(response) => response.status().to_string(),
+                Err(e) => e.to_string(),
+            };
+            attempt += 1;
+            log_error(&format!(
+                "{} request failed ({}), retry {}/{} in {:?}",
+                self.name, reason, attempt, self.settings.max_retries, delay
+            ));
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Full jitter: a random delay up to `retry_base * 2^attempt`, capped at `retry_max`.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .settings
+            .retry_base
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.settings.retry_max);
+        let millis = ceiling.as_millis() as u64;
+        Duration::from_millis(rand::thread_rng().gen_range(0..=millis))
+    }
+}
+
+/// Throttling, timeouts and server errors, including Anthropic's 529 "overloaded".
+fn is_retryable(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 408 | 429 | 500 | 502 | 503 | 504 | 529)
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+    retry_after_delay(value, Utc::now())
+}
+
+/// `Retry-After` as either a number of seconds or an HTTP date, which is no delay once past.
+fn retry_after_delay(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&Utc) - now).to_std().unwrap_or_default())
+}
+
+fn env_name(name: &str, key: &str) -> String {
+    format!("HTTP_{}_{}", name.to_uppercase().replace('-', "_"), key)
+}
+
+fn number<T: std::str::FromStr>(name: &str, key: &str) -> Result<Option<T>, String> {
+    for variable in [env_name(name, key), format!("HTTP_{}", key)] {
+        match std::env::var(&variable) {
+            Ok(value) if !value.is_empty() => {
+                return value
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| format!("{} must be a number", variable));
+            }
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+fn millis(name: &str, key: &str, default: Duration) -> Result<Duration, String> {
+    Ok(number(name, key)?.map_or(default, Duration::from_millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn client(retry_base_ms: u64, retry_max_ms: u64) -> HttpClient {
+        let settings = HttpSettings {
+            retry_base: Duration::from_millis(retry_base_ms),
+            retry_max: Duration::from_millis(retry_max_ms),
+            ..HttpSettings::default()
+        };
+        HttpClient::new("test", settings, None).unwrap()
+    }
+
+    #[test]
+    fn backs_off_up_to_double_the_last_ceiling() {
+        let client = client(100, 30_000);
+        for (attempt, ceiling) in [(0, 100), (1, 200), (2, 400), (5, 3_200)] {
+            let delays: Vec<Duration> = (0..200).map(|_| client.backoff(attempt)).collect();
+            assert!(delays
+                .iter()
+                .all(|delay| *delay <= Duration::from_millis(ceiling)));
+            // Full jitter spreads the delays over the whole range.
+            assert!(delays
+                .iter()
+                .any(|delay| *delay < Duration::from_millis(ceiling / 2)));
+            assert!(delays
+                .iter()
+                .any(|delay| *delay > Duration::from_millis(ceiling / 2)));
+        }
+    }
+
+    #[test]
+    fn caps_backoff_at_the_max_delay() {
+        let client = client(500, 2_000);
+        for attempt in [3, 10, 31, 64, u32::MAX] {
+            let delays: Vec<Duration> = (0..200).map(|_| client.backoff(attempt)).collect();
+            assert!(delays
+                .iter()
+                .all(|delay| *delay <= Duration::from_millis(2_000)));
+            assert!(delays
+                .iter()
+                .any(|delay| *delay > Duration::from_millis(1_000)));
+        }
+    }
+
+    #[test]
+    fn reads_retry_after_as_seconds() {
+        let now = Utc::now();
+        assert_eq!(
+            retry_after_delay("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(retry_after_delay(" 0 ", now), Some(Duration::ZERO));
+        assert_eq!(retry_after_delay("-5", now), None);
+        assert_eq!(retry_after_delay("soon", now), None);
+    }
+
+    #[test]
+    fn reads_retry_after_as_an_http_date() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 18, 7, 0, 0).unwrap();
+        assert_eq!(
+            retry_after_delay("Sun, 18 Oct 2026 07:01:30 GMT", now),
+            Some(Duration::from_secs(90))
+        );
+        // A date that has already passed means retry straight away.
+        assert_eq!(
+            retry_after_delay("Sun, 18 Oct 2026 06:59:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+    }
+}
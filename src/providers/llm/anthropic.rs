@@ -0,0 +1,167 @@
+use crate::{
+    constants::utility::{log_error, log_query},
+    models::{
+        anthropic_models::{
+            AnthropicErrorResponse, AnthropicRequest, AnthropicResponse, Message, Tool, ToolChoice,
+        },
+        llm_models::LlmRequest,
+    },
+    providers::{http::HttpClient, llm::LlmProvider},
+};
+use futures_util::future::BoxFuture;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use std::time::Instant;
+
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+pub const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// Anthropic's Messages API.
+pub struct Anthropic {
+    /// e.g. `https://api.anthropic.com/v1`.
+    pub base_url: String,
+    pub api_key: Option<String>,
+    /// The environment variable `api_key` is read from, named when it's missing.
+    pub api_key_env: String,
+    pub default_model: String,
+    pub max_tokens: u32,
+    pub http: HttpClient,
+}
+
+impl LlmProvider for Anthropic {
+    fn complete<'a>(&'a self, request: &'a LlmRequest) -> BoxFuture<'a, Result<String, String>> {
+        Box::pin(async move {
+            let start_time: Instant = Instant::now();
+            let api_key = self.api_key.as_ref().ok_or_else(|| {
+                let message = format!("{} not set", self.api_key_env);
+                log_error(&message);
+                message
+            })?;
+
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                "x-api-key",
+                HeaderValue::from_str(api_key).map_err(|_| "Invalid API key format".to_string())?,
+            );
+            headers.insert(
+                "anthropic-version",
+                HeaderValue::from_static(ANTHROPIC_VERSION),
+            );
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+            let model = request
+                .model
+                .clone()
+                .unwrap_or_else(|| self.default_model.clone());
+            let anthropic_request = AnthropicRequest {
+                model: model.clone(),
+                max_tokens: self.max_tokens,
+                system: request.system.clone().filter(|system| !system.is_empty()),
+                messages: vec![Message {
+                    role: "user".to_string(),
+                    content: request.prompt.clone(),
+                }],
+                tools: request
+                    .response_schema
+                    .iter()
+                    .map(|response_schema| Tool {
+                        name: response_schema.name.clone(),
+                        description: "Return the response in this structure.".to_string(),
+                        input_schema: response_schema.schema.clone(),
+                    })
+                    .collect(),
+                tool_choice: request
+                    .response_schema
+                    .as_ref()
+                    .map(|response_schema| ToolChoice {
+                        kind: "tool".to_string(),
+                        name: response_schema.name.clone(),
+                    }),
+            };
+
+            let url = format!("{}/messages", self.base_url.trim_end_matches('/'));
+            let response = self
+                .http
+                .send(|client| {
+                    client
+                        .post(&url)
+                        .headers(headers.clone())
+                        .json(&anthropic_request)
+                })
+                .await
+                .map_err(|e| {
+                    log_error(&format!("Request failed: {}", e));
+                    format!("Request failed: {}", e)
+                })?;
+
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                let message = match serde_json::from_str::<AnthropicErrorResponse>(&body) {
+                    Ok(error) => format!(
+                        "Anthropic error! status: {}, {}: {}",
+                        status, error.error.kind, error.error.message
+                    ),
+                    Err(_) => format!("HTTP error! status: {}", status),
+                };
+                log_error(&message);
+                return Err(message);
+            }
+
+            let anthropic_response: AnthropicResponse = response.json().await.map_err(|e| {
+                log_error(&format!("JSON parsing failed: {}", e));
+                format!("JSON parsing failed: {}", e)
+            })?;
+
+            let duration = start_time.elapsed();
+            log_query(&format!(
+                "Anthropic request took: {:?} with {} and {} characters",
+                duration,
+                model,
+                request.prompt.len()
+            ));
+            if anthropic_response.stop_reason.as_deref() == Some("max_tokens") {
+                log_error(&format!(
+                    "Anthropic response was cut off at {} tokens",
+                    self.max_tokens
+                ));
+            }
+
+            if request.response_schema.is_some() {
+                return anthropic_response
+                    .content
+                    .into_iter()
+                    .find_map(|block| block.input.filter(|_| block.kind == "tool_use"))
+                    .map(|input| input.to_string())
+                    .ok_or_else(|| {
+                        log_error("No tool_use content returned from Anthropic");
+                        "No tool_use content returned from Anthropic".to_string()
+                    });
+            }
+
+            let text = anthropic_response
+                .content
+                .into_iter()
+                .filter(|block| block.kind == "text")
+                .filter_map(|block| block.text)
+                .collect::<Vec<String>>()
+                .join("");
+            if text.is_empty() {
+                log_error("No text content returned from Anthropic");
+                return Err("No text content returned from Anthropic".to_string());
+            }
+            Ok(text)
+        })
+    }
+
+    fn default_model(&self) -> &str {
+        &self.default_model
+    }
+
+    fn base_url(&self) -> Option<&str> {
+        Some(&self.base_url)
+    }
+
+    fn is_available(&self) -> bool {
+        self.http.is_available()
+    }
+}
@@ -0,0 +1,26 @@
+use crate::models::llm_models::LlmRequest;
+use futures_util::future::BoxFuture;
+
+pub mod anthropic;
+pub mod open_ai;
+
+/// A language model the digest pipeline can prompt.
+pub trait LlmProvider: Send + Sync {
+    /// Returns the text of the model's reply.
+    fn complete<'a>(&'a self, request: &'a LlmRequest) -> BoxFuture<'a, Result<String, String>>;
+
+    /// The model used when a request doesn't name one.
+    fn default_model(&self) -> &str;
+
+    /// Where requests are sent, when it's configurable. Part of its response cache key, so two
+    /// servers with the same model names don't share entries.
+    fn base_url(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether the upstream's circuit breaker would let a call through, so a fallback chain
+    /// can skip it.
+    fn is_available(&self) -> bool {
+        true
+    }
+}
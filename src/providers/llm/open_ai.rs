@@ -0,0 +1,148 @@
+use crate::{
+    constants::utility::{log_error, log_query},
+    models::{
+        llm_models::LlmRequest,
+        open_ai_models::{JsonSchema, Message, OpenAiRequest, OpenAiResponse, ResponseFormat},
+    },
+    providers::{http::HttpClient, llm::LlmProvider},
+};
+use futures_util::future::BoxFuture;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use std::time::Instant;
+
+/// Any server exposing OpenAI's `/chat/completions`, such as OpenAI itself, Azure OpenAI,
+/// Ollama or llama.cpp.
+pub struct OpenAiCompatible {
+    /// e.g. `https://api.openai.com/v1`. A query string (like Azure's `api-version`) is kept.
+    pub base_url: String,
+    /// Sent as a bearer token, or as-is in `api_key_header` when set. Local servers don't need one.
+    pub api_key: Option<String>,
+    /// Header for the raw API key instead of `Authorization: Bearer`, e.g. `api-key` for Azure.
+    pub api_key_header: Option<String>,
+    pub default_model: String,
+    pub http: HttpClient,
+}
+
+impl OpenAiCompatible {
+    fn completions_url(&self) -> String {
+        match self.base_url.split_once('?') {
+            Some((base, query)) => {
+                format!("{}/chat/completions?{}", base.trim_end_matches('/'), query)
+            }
+            None => format!("{}/chat/completions", self.base_url.trim_end_matches('/')),
+        }
+    }
+
+    fn headers(&self) -> Result<HeaderMap, String> {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        if let Some(api_key) = &self.api_key {
+            let (name, value) = match &self.api_key_header {
+                Some(name) => (
+                    HeaderName::from_bytes(name.as_bytes())
+                        .map_err(|_| format!("Invalid API key header: {}", name))?,
+                    api_key.clone(),
+                ),
+                None => (AUTHORIZATION, format!("Bearer {}", api_key)),
+            };
+            headers.insert(
+                name,
+                HeaderValue::from_str(&value).map_err(|_| "Invalid API key format".to_string())?,
+            );
+        }
+        Ok(headers)
+    }
+}
+
+impl LlmProvider for OpenAiCompatible {
+    fn complete<'a>(&'a self, request: &'a LlmRequest) -> BoxFuture<'a, Result<String, String>> {
+        Box::pin(async move {
+            let start_time: Instant = Instant::now();
+            let model = request
+                .model
+                .clone()
+                .unwrap_or_else(|| self.default_model.clone());
+            let openai_request = OpenAiRequest {
+                model: model.clone(),
+                messages: vec![
+                    Message {
+                        role: "system".to_string(),
+                        content: request.system.clone().unwrap_or_default(),
+                    },
+                    Message {
+                        role: "user".to_string(),
+                        content: request.prompt.clone(),
+                    },
+                ],
+                response_format: request.response_schema.as_ref().map(|response_schema| {
+                    ResponseFormat {
+                        kind: "json_schema".to_string(),
+                        json_schema: JsonSchema {
+                            name: response_schema.name.clone(),
+                            schema: response_schema.schema.clone(),
+                            strict: true,
+                        },
+                    }
+                }),
+            };
+
+            let url = self.completions_url();
+            let headers = self.headers()?;
+            let response = self
+                .http
+                .send(|client| {
+                    client
+                        .post(&url)
+                        .headers(headers.clone())
+                        .json(&openai_request)
+                })
+                .await
+                .map_err(|e| {
+                    log_error(&format!("Request failed: {}", e));
+                    format!("Request failed: {}", e)
+                })?;
+
+            if !response.status().is_success() {
+                return Err(format!("HTTP error! status: {}", response.status()));
+            }
+
+            let most_relevant_data: OpenAiResponse = response.json().await.map_err(|e| {
+                log_error(&format!("JSON parsing failed: {}", e));
+                format!("JSON parsing failed: {}", e)
+            })?;
+
+            let duration = start_time.elapsed();
+            log_query(&format!(
+                "Transform request took: {:?} with {} and {} characters",
+                duration,
+                model,
+                request.prompt.len()
+            ));
+
+            most_relevant_data
+                .choices
+                .unwrap_or_default()
+                .into_iter()
+                .next()
+                .ok_or_else(|| "No choices returned from OpenAI".to_string())?
+                .message
+                .map(|message| message.content)
+                .ok_or_else(|| {
+                    log_error("Invalid response format from OpenAI");
+                    "Invalid response format from OpenAI".to_string()
+                })
+        })
+    }
+
+    fn default_model(&self) -> &str {
+        &self.default_model
+    }
+
+    fn base_url(&self) -> Option<&str> {
+        Some(&self.base_url)
+    }
+
+    fn is_available(&self) -> bool {
+        self.http.is_available()
+    }
+}
@@ -0,0 +1,304 @@
+use crate::{
+    constants::config::{
+        ANTHROPIC_BASE_URL, DEFAULT_ANTHROPIC_MODEL, DEFAULT_OPEN_AI_MODEL, OPEN_AI_BASE_URL,
+    },
+    models::circuit_models::CircuitStatus,
+    providers::{
+        cached::{CachedLlm, CachedSearch},
+        http::HttpClient,
+        llm::{
+            anthropic::{Anthropic, DEFAULT_MAX_TOKENS},
+            open_ai::OpenAiCompatible,
+            LlmProvider,
+        },
+        search::{
+            bing::BingSearch,
+            brave::BraveSearch,
+            meta::{MetaSearch, DEFAULT_META_SEARCH_PROVIDERS},
+            perplexity::{PerplexitySearch, SONAR_LARGE, SONAR_SMALL},
+            searxng::SearxngSearch,
+            SearchProvider,
+        },
+    },
+    storage::response_cache::ResponseCache,
+};
+use std::{collections::BTreeMap, sync::Arc};
+
+pub mod cached;
+pub mod cassette;
+pub mod circuit;
+pub mod http;
+pub mod llm;
+pub mod search;
+
+/// Named providers that digests and endpoints can pick from.
+pub struct Providers {
+    search: BTreeMap<String, Arc<dyn SearchProvider>>,
+    default_search: String,
+    llm: BTreeMap<String, Arc<dyn LlmProvider>>,
+    default_llm: String,
+    http: BTreeMap<String, HttpClient>,
+    cache: Arc<ResponseCache>,
+}
+
+impl Providers {
+    /// Registers the built-in providers. The default search provider is `SEARCH_PROVIDER`,
+    /// falling back to `searxng` when `SEARXNG_URL` is set or `brave` otherwise when `USE_OPEN_AI`
+    /// is `true`, and `perplexity` when it isn't. `meta`
+    /// combines the providers listed in `META_SEARCH_PROVIDERS`.
+    ///
+    /// LLM providers are `openai` and `anthropic` plus any named in `LLM_PROVIDERS` (comma
+    /// separated), each configured with `LLM_<NAME>_KIND` (`openai` or `anthropic`),
+    /// `LLM_<NAME>_BASE_URL`, `LLM_<NAME>_API_KEY`, `LLM_<NAME>_API_KEY_HEADER`,
+    /// `LLM_<NAME>_MODEL` and `LLM_<NAME>_MAX_TOKENS`. The default LLM provider is
+    /// `LLM_PROVIDER`, or `openai`.
+    ///
+    /// Each upstream gets its own HTTP client, configured with `HTTP_<NAME>_*` (see
+    /// [`HttpSettings::from_env`](http::HttpSettings::from_env)).
+    pub fn from_env() -> Result<Self, String> {
+        let mut http = BTreeMap::new();
+        for name in ["bing", "brave", "searxng", "perplexity"] {
+            http.insert(name.to_string(), HttpClient::from_env(name)?);
+        }
+
+        let mut search: BTreeMap<String, Arc<dyn SearchProvider>> = BTreeMap::new();
+        search.insert(
+            "bing".to_string(),
+            Arc::new(BingSearch::new(http["bing"].clone())),
+        );
+        search.insert(
+            "brave".to_string(),
+            Arc::new(BraveSearch::new(http["brave"].clone())),
+        );
+        search.insert(
+            "searxng".to_string(),
+            Arc::new(SearxngSearch {
+                base_url: std::env::var("SEARXNG_URL")
+                    .ok()
+                    .filter(|url| !url.is_empty()),
+                authorization: std::env::var("SEARXNG_AUTHORIZATION").ok(),
+                http: http["searxng"].clone(),
+            }),
+        );
+        search.insert(
+            "perplexity".to_string(),
+            Arc::new(PerplexitySearch {
+                model: SONAR_LARGE.to_string(),
+                http: http["perplexity"].clone(),
+            }),
+        );
+        search.insert(
+            "perplexity-small".to_string(),
+            Arc::new(PerplexitySearch {
+                model: SONAR_SMALL.to_string(),
+                http: http["perplexity"].clone(),
+            }),
+        );
+
+        let cache = Arc::new(ResponseCache::from_env());
+        let mut search = cached(search, |name, provider| {
+            Arc::new(CachedSearch {
+                name,
+                provider,
+                cache: cache.clone(),
+            })
+        });
+
+        let members = std::env::var("META_SEARCH_PROVIDERS")
+            .unwrap_or_else(|_| DEFAULT_META_SEARCH_PROVIDERS.to_string());
+        let members = members
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| lookup("search", &search, name))
+            .collect::<Result<Vec<_>, String>>()?;
+        search.insert(
+            "meta".to_string(),
+            Arc::new(MetaSearch { providers: members }),
+        );
+
+        let default_search = std::env::var("SEARCH_PROVIDER").unwrap_or_else(|_| {
+            if std::env::var("USE_OPEN_AI").unwrap_or_default() != "true" {
+                "perplexity".to_string()
+            } else if std::env::var("SEARXNG_URL").is_ok_and(|url| !url.is_empty()) {
+                "searxng".to_string()
+            } else {
+                "brave".to_string()
+            }
+        });
+
+        for name in ["openai", "anthropic"] {
+            http.insert(name.to_string(), HttpClient::from_env(name)?);
+        }
+        let mut llm: BTreeMap<String, Arc<dyn LlmProvider>> = BTreeMap::new();
+        llm.insert(
+            "openai".to_string(),
+            Arc::new(OpenAiCompatible {
+                base_url: llm_env("openai", "BASE_URL")
+                    .unwrap_or_else(|| OPEN_AI_BASE_URL.to_string()),
+                api_key: llm_env("openai", "API_KEY")
+                    .or_else(|| std::env::var("OPEN_AI_API_KEY").ok()),
+                api_key_header: llm_env("openai", "API_KEY_HEADER"),
+                default_model: llm_env("openai", "MODEL")
+                    .unwrap_or_else(|| DEFAULT_OPEN_AI_MODEL.to_string()),
+                http: http["openai"].clone(),
+            }),
+        );
+        llm.insert(
+            "anthropic".to_string(),
+            Arc::new(Anthropic {
+                base_url: llm_env("anthropic", "BASE_URL")
+                    .unwrap_or_else(|| ANTHROPIC_BASE_URL.to_string()),
+                api_key: llm_env("anthropic", "API_KEY")
+                    .or_else(|| std::env::var("ANTHROPIC_API_KEY").ok()),
+                api_key_env: "ANTHROPIC_API_KEY".to_string(),
+                default_model: llm_env("anthropic", "MODEL")
+                    .unwrap_or_else(|| DEFAULT_ANTHROPIC_MODEL.to_string()),
+                max_tokens: max_tokens("anthropic")?,
+                http: http["anthropic"].clone(),
+            }),
+        );
+        let names = std::env::var("LLM_PROVIDERS").unwrap_or_default();
+        for name in names.split(',').map(str::trim) {
+            if name.is_empty() || llm.contains_key(name) {
+                continue;
+            }
+            let base_url = llm_env(name, "BASE_URL")
+                .ok_or_else(|| format!("{} is not set", llm_env_name(name, "BASE_URL")))?;
+            let default_model = llm_env(name, "MODEL")
+                .ok_or_else(|| format!("{} is not set", llm_env_name(name, "MODEL")))?;
+            let client = HttpClient::from_env(name)?;
+            let provider: Arc<dyn LlmProvider> =
+                match llm_env(name, "KIND").as_deref().unwrap_or("openai") {
+                    "openai" => Arc::new(OpenAiCompatible {
+                        base_url,
+                        api_key: llm_env(name, "API_KEY"),
+                        api_key_header: llm_env(name, "API_KEY_HEADER"),
+                        default_model,
+                        http: client.clone(),
+                    }),
+                    "anthropic" => Arc::new(Anthropic {
+                        base_url,
+                        api_key: llm_env(name, "API_KEY"),
+                        api_key_env: llm_env_name(name, "API_KEY"),
+                        default_model,
+                        max_tokens: max_tokens(name)?,
+                        http: client.clone(),
+                    }),
+                    kind => {
+                        return Err(format!(
+                            "{} must be openai or anthropic, got '{}'",
+                            llm_env_name(name, "KIND"),
+                            kind
+                        ))
+                    }
+                };
+            llm.insert(name.to_string(), provider);
+            http.insert(name.to_string(), client);
+        }
+        let llm = cached(llm, |name, provider| {
+            Arc::new(CachedLlm {
+                name,
+                provider,
+                cache: cache.clone(),
+            })
+        });
+        let default_llm = std::env::var("LLM_PROVIDER").unwrap_or_else(|_| "openai".to_string());
+
+        Providers::new((search, default_search), (llm, default_llm), http, cache)
+    }
+
+    /// Providers from explicit parts, failing if either default isn't one of them. `http` is
+    /// every upstream's client, listed by the status endpoint.
+    pub fn new(
+        (search, default_search): (BTreeMap<String, Arc<dyn SearchProvider>>, String),
+        (llm, default_llm): (BTreeMap<String, Arc<dyn LlmProvider>>, String),
+        http: BTreeMap<String, HttpClient>,
+        cache: Arc<ResponseCache>,
+    ) -> Result<Self, String> {
+        let providers = Providers {
+            search,
+            default_search,
+            llm,
+            default_llm,
+            http,
+            cache,
+        };
+        providers.search(None)?;
+        providers.llm(None)?;
+        Ok(providers)
+    }
+
+    /// Looks up a search provider by name, or the default one when `name` is `None`.
+    pub fn search(&self, name: Option<&str>) -> Result<(String, Arc<dyn SearchProvider>), String> {
+        lookup("search", &self.search, name.unwrap_or(&self.default_search))
+    }
+
+    /// Looks up an LLM provider by name, or the default one when `name` is `None`.
+    pub fn llm(&self, name: Option<&str>) -> Result<(String, Arc<dyn LlmProvider>), String> {
+        lookup("LLM", &self.llm, name.unwrap_or(&self.default_llm))
+    }
+
+    /// Circuit breaker state of every upstream.
+    pub fn circuits(&self) -> Vec<CircuitStatus> {
+        self.http.values().map(HttpClient::circuit).collect()
+    }
+
+    pub fn cache(&self) -> Arc<ResponseCache> {
+        self.cache.clone()
+    }
+
+    /// The shared HTTP client for an upstream, for callers that build a provider per request.
+    pub fn http(&self, name: &str) -> Option<HttpClient> {
+        self.http.get(name).cloned()
+    }
+}
+
+fn lookup<T: ?Sized>(
+    kind: &str,
+    providers: &BTreeMap<String, Arc<T>>,
+    name: &str,
+) -> Result<(String, Arc<T>), String> {
+    providers
+        .get(name)
+        .map(|provider| (name.to_string(), provider.clone()))
+        .ok_or_else(|| {
+            format!(
+                "Unknown {} provider '{}', expected one of: {}",
+                kind,
+                name,
+                providers.keys().cloned().collect::<Vec<_>>().join(", ")
+            )
+        })
+}
+
+/// Wraps every provider in the response cache. `meta` is added afterwards so its members are
+/// cached individually.
+fn cached<T: ?Sized>(
+    providers: BTreeMap<String, Arc<T>>,
+    wrap: impl Fn(String, Arc<T>) -> Arc<T>,
+) -> BTreeMap<String, Arc<T>> {
+    providers
+        .into_iter()
+        .map(|(name, provider)| (name.clone(), wrap(name, provider)))
+        .collect()
+}
+
+fn llm_env_name(name: &str, key: &str) -> String {
+    format!("LLM_{}_{}", name.to_uppercase().replace('-', "_"), key)
+}
+
+fn llm_env(name: &str, key: &str) -> Option<String> {
+    std::env::var(llm_env_name(name, key))
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+fn max_tokens(name: &str) -> Result<u32, String> {
+    match llm_env(name, "MAX_TOKENS") {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("{} must be a number", llm_env_name(name, "MAX_TOKENS"))),
+        None => Ok(DEFAULT_MAX_TOKENS),
+    }
+}
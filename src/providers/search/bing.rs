@@ -0,0 +1,121 @@
+use crate::{
+    constants::{
+        config::BING_SEARCH_ENDPOINT,
+        utility::{log_error, log_query},
+    },
+    models::{
+        bing_models::{BingSearchResponse, WebPage},
+        search_models::{Freshness, WebSearchRequest, WebSearchResponse, WebSearchResult},
+    },
+    providers::{http::HttpClient, search::SearchProvider},
+};
+use futures_util::future::BoxFuture;
+use reqwest::header::{HeaderMap, HeaderValue};
+use std::time::Instant;
+use url::form_urlencoded;
+
+pub struct BingSearch {
+    pub endpoint: String,
+    pub http: HttpClient,
+}
+
+impl BingSearch {
+    pub fn new(http: HttpClient) -> Self {
+        BingSearch {
+            endpoint: BING_SEARCH_ENDPOINT.to_string(),
+            http,
+        }
+    }
+}
+
+impl SearchProvider for BingSearch {
+    fn search<'a>(
+        &'a self,
+        request: &'a WebSearchRequest,
+    ) -> BoxFuture<'a, Result<WebSearchResponse, String>> {
+        Box::pin(async move {
+            let start_time: Instant = Instant::now();
+            let api_key = std::env::var("BING_API_KEY").map_err(|_| {
+                log_error("BING_API_KEY not set");
+                "BING_API_KEY not set".to_string()
+            })?;
+
+            let mut headers = HeaderMap::new();
+            match HeaderValue::from_str(&api_key) {
+                Ok(value) => headers.insert("Ocp-Apim-Subscription-Key", value),
+                Err(_) => {
+                    log_error("Invalid API key format");
+                    return Err("Invalid API key format".to_string());
+                }
+            };
+
+            let query_string = {
+                let mut params = form_urlencoded::Serializer::new(String::new());
+                params.append_pair("q", request.query.as_str());
+                if let Some(count) = request.count {
+                    params.append_pair("count", &count.to_string());
+                }
+                if let Some(freshness) = request.freshness {
+                    params.append_pair(
+                        "freshness",
+                        match freshness {
+                            Freshness::Day => "Day",
+                            Freshness::Week => "Week",
+                            Freshness::Month => "Month",
+                        },
+                    );
+                }
+                if let Some(market) = &request.market {
+                    params.append_pair("mkt", market);
+                }
+                params.finish()
+            };
+            let url = format!("{}?{}", self.endpoint, query_string);
+
+            let search_response = self
+                .http
+                .send(|client| client.get(&url).headers(headers.clone()))
+                .await
+                .map_err(|e| {
+                    log_error(&format!("Request failed: {}", e));
+                    format!("Request failed: {}", e)
+                })?;
+
+            if !search_response.status().is_success() {
+                log_error(&format!("HTTP error! status: {}", search_response.status()));
+                return Err(format!("HTTP error! status: {}", search_response.status()));
+            }
+
+            let response = search_response
+                .json::<BingSearchResponse>()
+                .await
+                .map_err(|e| format!("Failed to get response text: {}", e))?;
+            let duration = start_time.elapsed();
+            log_query(&format!("Bing request took: {:?}", duration));
+
+            Ok(WebSearchResponse {
+                results: response
+                    .web_pages
+                    .value
+                    .into_iter()
+                    .map(to_result)
+                    .collect(),
+                answer: None,
+            })
+        })
+    }
+
+    fn is_available(&self) -> bool {
+        self.http.is_available()
+    }
+}
+
+fn to_result(page: WebPage) -> WebSearchResult {
+    WebSearchResult {
+        title: page.name,
+        url: page.url,
+        snippet: page.snippet,
+        published: page.date_published,
+        sources: Vec::new(),
+    }
+}
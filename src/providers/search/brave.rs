@@ -0,0 +1,130 @@
+use crate::{
+    constants::{
+        config::BRAVE_SEARCH_ENDPOINT,
+        utility::{log_error, log_query},
+    },
+    models::{
+        brave_models::{BraveSearchResponse, BraveWebResult},
+        search_models::{Freshness, WebSearchRequest, WebSearchResponse, WebSearchResult},
+    },
+    providers::{http::HttpClient, search::SearchProvider},
+};
+use futures_util::future::BoxFuture;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT};
+use std::time::Instant;
+use url::form_urlencoded;
+
+/// Brave caps `count` at 20.
+pub const MAX_BRAVE_COUNT: u32 = 20;
+
+pub struct BraveSearch {
+    pub endpoint: String,
+    pub http: HttpClient,
+}
+
+impl BraveSearch {
+    pub fn new(http: HttpClient) -> Self {
+        BraveSearch {
+            endpoint: BRAVE_SEARCH_ENDPOINT.to_string(),
+            http,
+        }
+    }
+}
+
+impl SearchProvider for BraveSearch {
+    fn search<'a>(
+        &'a self,
+        request: &'a WebSearchRequest,
+    ) -> BoxFuture<'a, Result<WebSearchResponse, String>> {
+        Box::pin(async move {
+            let start_time: Instant = Instant::now();
+            let api_key = std::env::var("BRAVE_API_KEY").map_err(|_| {
+                log_error("BRAVE_API_KEY not set");
+                "BRAVE_API_KEY not set".to_string()
+            })?;
+
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                "X-Subscription-Token",
+                HeaderValue::from_str(&api_key).map_err(|_| {
+                    log_error("Invalid API key format");
+                    "Invalid API key format".to_string()
+                })?,
+            );
+            headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+
+            let query_string = {
+                let mut params = form_urlencoded::Serializer::new(String::new());
+                params.append_pair("q", request.query.as_str());
+                if let Some(count) = request.count {
+                    params.append_pair("count", &count.min(MAX_BRAVE_COUNT).to_string());
+                }
+                if let Some(freshness) = request.freshness {
+                    params.append_pair(
+                        "freshness",
+                        match freshness {
+                            Freshness::Day => "pd",
+                            Freshness::Week => "pw",
+                            Freshness::Month => "pm",
+                        },
+                    );
+                }
+                // Brave splits a market like `en-AU` into a language and a country.
+                if let Some((language, country)) =
+                    request.market.as_deref().and_then(|m| m.split_once('-'))
+                {
+                    params.append_pair("search_lang", &language.to_lowercase());
+                    params.append_pair("country", &country.to_uppercase());
+                }
+                params.finish()
+            };
+            let url = format!("{}?{}", self.endpoint, query_string);
+
+            let search_response = self
+                .http
+                .send(|client| client.get(&url).headers(headers.clone()))
+                .await
+                .map_err(|e| {
+                    log_error(&format!("Request failed: {}", e));
+                    format!("Request failed: {}", e)
+                })?;
+
+            if !search_response.status().is_success() {
+                log_error(&format!("HTTP error! status: {}", search_response.status()));
+                return Err(format!("HTTP error! status: {}", search_response.status()));
+            }
+
+            let response = search_response
+                .json::<BraveSearchResponse>()
+                .await
+                .map_err(|e| format!("Failed to get response text: {}", e))?;
+            let duration = start_time.elapsed();
+            log_query(&format!("Brave request took: {:?}", duration));
+
+            Ok(WebSearchResponse {
+                results: response
+                    .web
+                    .map(|web| web.results)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(to_result)
+                    .collect(),
+                answer: None,
+            })
+        })
+    }
+
+    fn is_available(&self) -> bool {
+        self.http.is_available()
+    }
+}
+
+fn to_result(result: BraveWebResult) -> WebSearchResult {
+    WebSearchResult {
+        title: result.title,
+        url: result.url,
+        snippet: result.description,
+        published: result.page_age.or(result.age),
+        sources: Vec::new(),
+    }
+}
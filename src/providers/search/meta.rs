@@ -0,0 +1,310 @@
+use crate::{
+    constants::utility::{log_error, log_query},
+    models::search_models::{WebSearchRequest, WebSearchResponse, WebSearchResult},
+    providers::search::SearchProvider,
+};
+use futures_util::future::{join_all, BoxFuture};
+use std::{collections::HashMap, sync::Arc, time::Instant};
+use url::Url;
+
+pub const DEFAULT_META_SEARCH_PROVIDERS: &str = "brave,searxng";
+/// Damps the weight of top ranks in reciprocal rank fusion, 60 is the value from the original paper.
+pub const RRF_K: f64 = 60.0;
+/// Query parameters that only track where a click came from.
+const TRACKING_PARAMS: [&str; 4] = ["fbclid", "gclid", "mc_cid", "mc_eid"];
+
+/// Fans a query out to several providers in parallel and merges their results with reciprocal
+/// rank fusion. Results are deduplicated by normalised URL and list every provider that
+/// returned them in `sources`. Fails only when every provider fails.
+pub struct MetaSearch {
+    pub providers: Vec<(String, Arc<dyn SearchProvider>)>,
+}
+
+struct FusedResult {
+    result: WebSearchResult,
+    score: f64,
+    first_seen: usize,
+}
+
+impl SearchProvider for MetaSearch {
+    fn search<'a>(
+        &'a self,
+        request: &'a WebSearchRequest,
+    ) -> BoxFuture<'a, Result<WebSearchResponse, String>> {
+        Box::pin(async move {
+            let start_time: Instant = Instant::now();
+            let responses = join_all(
+                self.providers
+                    .iter()
+                    .map(|(_, provider)| provider.search(request)),
+            )
+            .await;
+
+            let mut fused: HashMap<String, FusedResult> = HashMap::new();
+            let mut errors = Vec::new();
+            for ((name, _), response) in self.providers.iter().zip(responses) {
+                let response = match response {
+                    Ok(response) => response,
+                    Err(e) => {
+                        log_error(&format!("Meta-search provider {} failed: {}", name, e));
+                        errors.push(format!("{}: {}", name, e));
+                        continue;
+                    }
+                };
+                for (rank, result) in response.results.into_iter().enumerate() {
+                    let score = 1.0 / (RRF_K + rank as f64 + 1.0);
+                    let first_seen = fused.len();
+                    let entry =
+                        fused
+                            .entry(normalise_url(&result.url))
+                            .or_insert_with(|| FusedResult {
+                                result: WebSearchResult {
+                                    sources: Vec::new(),
+                                    ..result.clone()
+                                },
+                                score: 0.0,
+                                first_seen,
+                            });
+                    entry.score += score;
+                    if !entry.result.sources.contains(name) {
+                        entry.result.sources.push(name.clone());
+                    }
+                    if entry.result.published.is_none() {
+                        entry.result.published = result.published;
+                    }
+                    if entry.result.snippet.is_empty() {
+                        entry.result.snippet = result.snippet;
+                    }
+                }
+            }
+            if errors.len() == self.providers.len() {
+                return Err(format!(
+                    "All search providers failed: {}",
+                    errors.join("; ")
+                ));
+            }
+
+            let mut fused: Vec<FusedResult> = fused.into_values().collect();
+            fused.sort_by(|a, b| {
+                b.score
+                    .total_cmp(&a.score)
+                    .then(a.first_seen.cmp(&b.first_seen))
+            });
+            let count = request.count.map_or(usize::MAX, |count| count as usize);
+
+            let duration = start_time.elapsed();
+            log_query(&format!(
+                "Meta-search took: {:?} with {} results from {} providers",
+                duration,
+                fused.len(),
+                self.providers.len() - errors.len()
+            ));
+
+            Ok(WebSearchResponse {
+                results: fused
+                    .into_iter()
+                    .take(count)
+                    .map(|fused| fused.result)
+                    .collect(),
+                answer: None,
+            })
+        })
+    }
+
+    fn is_available(&self) -> bool {
+        self.providers
+            .iter()
+            .any(|(_, provider)| provider.is_available())
+    }
+}
+
+/// Reduces a URL to what identifies the page: lowercase host without `www.`, no fragment,
+/// no tracking parameters and no trailing slash. Unparseable URLs are only trimmed.
+pub fn normalise_url(url: &str) -> String {
+    let mut parsed = match Url::parse(url.trim()) {
+        Ok(parsed) => parsed,
+        Err(_) => return url.trim().trim_end_matches('/').to_lowercase(),
+    };
+    parsed.set_fragment(None);
+    let params: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(key, _)| !key.starts_with("utm_") && !TRACKING_PARAMS.contains(&key.as_ref()))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    let host = parsed.host_str().unwrap_or_default().to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    let mut normalised = format!("{}{}", host, parsed.path().trim_end_matches('/'));
+    if !params.is_empty() {
+        let query = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(params)
+            .finish();
+        normalised = format!("{}?{}", normalised, query);
+    }
+    normalised
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the same results, or error, for every query.
+    struct FixedSearch(Result<Vec<&'static str>, &'static str>);
+
+    impl SearchProvider for FixedSearch {
+        fn search<'a>(
+            &'a self,
+            _request: &'a WebSearchRequest,
+        ) -> BoxFuture<'a, Result<WebSearchResponse, String>> {
+            Box::pin(async move {
+                let urls = self.0.clone().map_err(str::to_string)?;
+                Ok(WebSearchResponse {
+                    results: urls
+                        .into_iter()
+                        .map(|url| WebSearchResult {
+                            title: url.to_string(),
+                            url: url.to_string(),
+                            snippet: String::new(),
+                            published: None,
+                            sources: Vec::new(),
+                        })
+                        .collect(),
+                    answer: None,
+                })
+            })
+        }
+    }
+
+    fn meta(providers: Vec<(&str, Result<Vec<&'static str>, &'static str>)>) -> MetaSearch {
+        MetaSearch {
+            providers: providers
+                .into_iter()
+                .map(|(name, results)| {
+                    let provider: Arc<dyn SearchProvider> = Arc::new(FixedSearch(results));
+                    (name.to_string(), provider)
+                })
+                .collect(),
+        }
+    }
+
+    async fn search(meta: &MetaSearch) -> Result<Vec<(String, Vec<String>)>, String> {
+        let response = meta.search(&WebSearchRequest::default()).await?;
+        Ok(response
+            .results
+            .into_iter()
+            .map(|result| (result.url, result.sources))
+            .collect())
+    }
+
+    #[test]
+    fn normalises_urls_to_the_page() {
+        let page = "example.com/grants";
+        for url in [
+            "https://example.com/grants",
+            "http://example.com/grants",
+            "https://www.example.com/grants",
+            "https://WWW.Example.com/grants/",
+            " https://example.com/grants#apply ",
+            "https://example.com/grants?utm_source=news&utm_medium=email",
+            "https://example.com/grants?fbclid=abc&gclid=def&mc_cid=1&mc_eid=2",
+        ] {
+            assert_eq!(normalise_url(url), page, "{}", url);
+        }
+        assert_eq!(
+            normalise_url("https://example.com/grants?round=2&utm_source=news"),
+            "example.com/grants?round=2"
+        );
+        assert_eq!(normalise_url("not a url/"), "not a url");
+    }
+
+    #[actix_web::test]
+    async fn merges_duplicates_across_providers() {
+        let meta = meta(vec![
+            ("brave", Ok(vec!["https://www.a.example/page/"])),
+            ("searxng", Ok(vec!["http://a.example/page?utm_source=x"])),
+        ]);
+        assert_eq!(
+            search(&meta).await.unwrap(),
+            [(
+                "https://www.a.example/page/".to_string(),
+                vec!["brave".to_string(), "searxng".to_string()]
+            )]
+        );
+    }
+
+    #[actix_web::test]
+    async fn orders_results_by_reciprocal_rank_fusion() {
+        let meta = meta(vec![
+            (
+                "brave",
+                Ok(vec![
+                    "https://x.example",
+                    "https://y.example",
+                    "https://z.example",
+                ]),
+            ),
+            (
+                "searxng",
+                Ok(vec!["https://y.example", "https://w.example"]),
+            ),
+        ]);
+        let urls: Vec<String> = search(&meta)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(url, _)| url)
+            .collect();
+        // y is ranked by both, and w's second place beats z's third.
+        assert_eq!(
+            urls,
+            [
+                "https://y.example",
+                "https://x.example",
+                "https://w.example",
+                "https://z.example"
+            ]
+        );
+    }
+
+    #[actix_web::test]
+    async fn breaks_ties_by_first_seen() {
+        let meta = meta(vec![
+            ("brave", Ok(vec!["https://b.example"])),
+            ("searxng", Ok(vec!["https://a.example"])),
+        ]);
+        let urls: Vec<String> = search(&meta)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(url, _)| url)
+            .collect();
+        assert_eq!(urls, ["https://b.example", "https://a.example"]);
+    }
+
+    #[actix_web::test]
+    async fn records_only_the_providers_that_returned_each_hit() {
+        let meta = meta(vec![
+            ("brave", Ok(vec!["https://a.example", "https://b.example"])),
+            ("searxng", Err("timed out")),
+            ("bing", Ok(vec!["https://b.example"])),
+        ]);
+        assert_eq!(
+            search(&meta).await.unwrap(),
+            [
+                (
+                    "https://b.example".to_string(),
+                    vec!["brave".to_string(), "bing".to_string()]
+                ),
+                ("https://a.example".to_string(), vec!["brave".to_string()]),
+            ]
+        );
+    }
+
+    #[actix_web::test]
+    async fn fails_only_when_every_provider_fails() {
+        let meta = meta(vec![("brave", Err("401")), ("searxng", Err("timed out"))]);
+        assert_eq!(
+            search(&meta).await.unwrap_err(),
+            "All search providers failed: brave: 401; searxng: timed out"
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use crate::models::search_models::{WebSearchRequest, WebSearchResponse};
+use futures_util::future::BoxFuture;
+
+pub mod bing;
+pub mod brave;
+pub mod meta;
+pub mod perplexity;
+pub mod searxng;
+
+/// A web search engine the digest pipeline can query.
+pub trait SearchProvider: Send + Sync {
+    fn search<'a>(
+        &'a self,
+        request: &'a WebSearchRequest,
+    ) -> BoxFuture<'a, Result<WebSearchResponse, String>>;
+
+    /// The model behind the provider, if it has one. Part of its response cache key.
+    fn model(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether the upstream's circuit breaker would let a call through, so a fallback chain
+    /// can skip it.
+    fn is_available(&self) -> bool {
+        true
+    }
+}
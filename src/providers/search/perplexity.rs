@@ -0,0 +1,131 @@
+use crate::{
+    constants::{
+        config::PERPLEXITY_SEARCH_ENDPOINT,
+        utility::{log_error, log_query},
+    },
+    models::{
+        perplexity_models::{Message, PerplexityRequest, PerplexityResponse},
+        search_models::{Freshness, WebSearchRequest, WebSearchResponse, WebSearchResult},
+    },
+    providers::{http::HttpClient, search::SearchProvider},
+};
+use futures_util::future::BoxFuture;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use std::time::Instant;
+
+pub const SONAR_SMALL: &str = "llama-3-sonar-small-32k-online";
+pub const SONAR_LARGE: &str = "llama-3-sonar-large-32k-online";
+const PROMPT_RULES: &str =
+    ". Transform the response into markdown and add the url at the end of each item.";
+
+/// Searches and summarises in one call, so results come back as a markdown `answer`.
+pub struct PerplexitySearch {
+    pub model: String,
+    pub http: HttpClient,
+}
+
+impl SearchProvider for PerplexitySearch {
+    fn search<'a>(
+        &'a self,
+        request: &'a WebSearchRequest,
+    ) -> BoxFuture<'a, Result<WebSearchResponse, String>> {
+        Box::pin(async move {
+            let start_time: Instant = Instant::now();
+            let api_key = std::env::var("PERPLEXITY_API_KEY")
+                .map_err(|_| "PERPLEXITY_API_KEY not set".to_string())?;
+
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {}", api_key))
+                    .map_err(|_| "Invalid API key format".to_string())?,
+            );
+            headers.insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/json; charset=utf-8"),
+            );
+
+            let perplexity_request = PerplexityRequest {
+                model: self.model.clone(),
+                messages: vec![Message {
+                    role: "user".to_string(),
+                    content: format!("{}{}", request.query, PROMPT_RULES),
+                }],
+                search_recency_filter: request.freshness.map(|freshness| {
+                    match freshness {
+                        Freshness::Day => "day",
+                        Freshness::Week => "week",
+                        Freshness::Month => "month",
+                    }
+                    .to_string()
+                }),
+            };
+
+            let perplexity_response = self
+                .http
+                .send(|client| {
+                    client
+                        .post(PERPLEXITY_SEARCH_ENDPOINT)
+                        .headers(headers.clone())
+                        .json(&perplexity_request)
+                })
+                .await
+                .map_err(|e| {
+                    log_error(&format!("Request failed: {}", e));
+                    format!("Request failed: {}", e)
+                })?;
+
+            if !perplexity_response.status().is_success() {
+                return Err(format!(
+                    "HTTP error! status: {}",
+                    perplexity_response.status()
+                ));
+            }
+
+            let duration = start_time.elapsed();
+            log_query(&format!(
+                "Perplexity request took: {:?} with {} and {} characters",
+                duration,
+                perplexity_request.model,
+                perplexity_request.messages[0].content.len()
+            ));
+
+            let response_content: PerplexityResponse = perplexity_response
+                .json()
+                .await
+                .map_err(|e| format!("JSON parsing failed: {}", e))?;
+
+            let answer = response_content
+                .choices
+                .first()
+                .map(|choice| choice.message.content.clone())
+                .ok_or_else(|| {
+                    log_error("No content");
+                    "No content".to_string()
+                })?;
+
+            Ok(WebSearchResponse {
+                results: response_content
+                    .citations
+                    .into_iter()
+                    .map(|url| WebSearchResult {
+                        title: url.clone(),
+                        url,
+                        snippet: String::new(),
+                        published: None,
+                        sources: Vec::new(),
+                    })
+                    .collect(),
+                answer: Some(answer),
+            })
+        })
+    }
+
+    fn model(&self) -> Option<&str> {
+        Some(&self.model)
+    }
+
+    fn is_available(&self) -> bool {
+        self.http.is_available()
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    constants::utility::{log_error, log_query},
+    models::{
+        search_models::{Freshness, WebSearchRequest, WebSearchResponse, WebSearchResult},
+        searxng_models::{SearxngResult, SearxngSearchResponse},
+    },
+    providers::{http::HttpClient, search::SearchProvider},
+};
+use futures_util::future::BoxFuture;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION};
+use std::time::Instant;
+use url::form_urlencoded;
+
+/// A self-hosted SearxNG instance, which needs `json` in its `search.formats`.
+pub struct SearxngSearch {
+    /// `SEARXNG_URL`, searches fail when it isn't set.
+    pub base_url: Option<String>,
+    /// `SEARXNG_AUTHORIZATION`, for instances behind a proxy with basic or bearer auth.
+    pub authorization: Option<String>,
+    pub http: HttpClient,
+}
+
+impl SearchProvider for SearxngSearch {
+    fn search<'a>(
+        &'a self,
+        request: &'a WebSearchRequest,
+    ) -> BoxFuture<'a, Result<WebSearchResponse, String>> {
+        Box::pin(async move {
+            let start_time: Instant = Instant::now();
+            let base_url = self.base_url.as_ref().ok_or_else(|| {
+                log_error("SEARXNG_URL not set");
+                "SEARXNG_URL not set".to_string()
+            })?;
+
+            let mut headers = HeaderMap::new();
+            headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+            if let Some(authorization) = &self.authorization {
+                headers.insert(
+                    AUTHORIZATION,
+                    HeaderValue::from_str(authorization).map_err(|_| {
+                        log_error("Invalid SEARXNG_AUTHORIZATION format");
+                        "Invalid SEARXNG_AUTHORIZATION format".to_string()
+                    })?,
+                );
+            }
+
+            let query_string = {
+                let mut params = form_urlencoded::Serializer::new(String::new());
+                params.append_pair("q", request.query.as_str());
+                params.append_pair("format", "json");
+                if let Some(freshness) = request.freshness {
+                    params.append_pair(
+                        "time_range",
+                        match freshness {
+                            Freshness::Day => "day",
+                            Freshness::Week => "week",
+                            Freshness::Month => "month",
+                        },
+                    );
+                }
+                if let Some(market) = &request.market {
+                    params.append_pair("language", market);
+                }
+                params.finish()
+            };
+            let url = format!("{}/search?{}", base_url.trim_end_matches('/'), query_string);
+
+            let search_response = self
+                .http
+                .send(|client| client.get(&url).headers(headers.clone()))
+                .await
+                .map_err(|e| {
+                    log_error(&format!("Request failed: {}", e));
+                    format!("Request failed: {}", e)
+                })?;
+
+            if !search_response.status().is_success() {
+                log_error(&format!("HTTP error! status: {}", search_response.status()));
+                return Err(format!("HTTP error! status: {}", search_response.status()));
+            }
+
+            let response = search_response
+                .json::<SearxngSearchResponse>()
+                .await
+                .map_err(|e| format!("Failed to get response text: {}", e))?;
+            let duration = start_time.elapsed();
+            log_query(&format!("SearxNG request took: {:?}", duration));
+
+            // SearxNG has no result count parameter, so trim locally.
+            let count = request.count.map_or(usize::MAX, |count| count as usize);
+            Ok(WebSearchResponse {
+                results: response
+                    .results
+                    .into_iter()
+                    .take(count)
+                    .map(to_result)
+                    .collect(),
+                answer: None,
+            })
+        })
+    }
+
+    fn is_available(&self) -> bool {
+        self.http.is_available()
+    }
+}
+
+fn to_result(result: SearxngResult) -> WebSearchResult {
+    WebSearchResult {
+        title: result.title,
+        url: result.url,
+        snippet: result.content,
+        published: result.published_date,
+        sources: Vec::new(),
+    }
+}
@@ -0,0 +1,300 @@
+use crate::{
+    constants::utility::log_error,
+    models::{
+        digest_models::{
+            CreateDigestRequest, GenerateOptions, PreviewRequest, RunOptions, Schedule,
+            UpdateDigestRequest,
+        },
+        email_models::Recipients,
+        job_models::JobTrigger,
+    },
+    providers::Providers,
+    scheduler::{runner::run_digests, schedule::validate_schedule},
+    services::{
+        digest::{digest_options, generate_digest, provider_chain, MAX_QUERIES_LIMIT},
+        email::{parse_recipients, Mailer},
+    },
+    storage::{
+        digest_store::DigestStore,
+        job_store::{DigestReporter, JobStore},
+        run_store::RunStore,
+    },
+};
+use actix_web::{rt::spawn, web, HttpResponse, ResponseError};
+use serde::de::DeserializeOwned;
+
+pub async fn list_digests(digest_store: web::Data<DigestStore>) -> HttpResponse {
+    HttpResponse::Ok().json(digest_store.list())
+}
+
+pub async fn get_digest(
+    digest_store: web::Data<DigestStore>,
+    id: web::Path<String>,
+) -> HttpResponse {
+    match digest_store.get(&id) {
+        Some(digest) => HttpResponse::Ok().json(digest),
+        None => HttpResponse::NotFound().body("Digest not found"),
+    }
+}
+
+pub async fn create_digest(
+    digest_store: web::Data<DigestStore>,
+    providers: web::Data<Providers>,
+    request: web::Json<CreateDigestRequest>,
+) -> HttpResponse {
+    let request = request.into_inner();
+    if let Err(message) = validate_fields(
+        Some(&request.topic),
+        Some(&request.subject),
+        Some(&request.schedule),
+        Addressing {
+            send_to: Some(&request.send_to),
+            cc: request.cc.as_ref(),
+            bcc: request.bcc.as_ref(),
+            reply_to: request.reply_to.as_ref(),
+            from_name: request.from_name.as_ref(),
+        },
+        &GenerateOptions {
+            search_provider: request.search_provider.clone(),
+            llm_provider: request.llm_provider.clone(),
+            model: request.model.clone(),
+            max_queries: request.max_queries,
+            failure_footer: request.failure_footer,
+            fallbacks: request.fallbacks.clone(),
+        },
+        &providers,
+    ) {
+        return HttpResponse::BadRequest().body(message);
+    }
+
+    match digest_store.create(request) {
+        Ok(digest) => HttpResponse::Created().json(digest),
+        Err(e) => {
+            log_error(&format!("Failed to save digest: {}", e));
+            HttpResponse::InternalServerError().body(format!("Failed to save digest: {}", e))
+        }
+    }
+}
+
+pub async fn update_digest(
+    digest_store: web::Data<DigestStore>,
+    providers: web::Data<Providers>,
+    id: web::Path<String>,
+    request: web::Json<UpdateDigestRequest>,
+) -> HttpResponse {
+    let request = request.into_inner();
+    if let Err(message) = validate_fields(
+        request.topic.as_ref(),
+        request.subject.as_ref(),
+        request.schedule.as_ref(),
+        Addressing {
+            send_to: request.send_to.as_ref(),
+            cc: request.cc.as_ref().and_then(Option::as_ref),
+            bcc: request.bcc.as_ref().and_then(Option::as_ref),
+            reply_to: request.reply_to.as_ref().and_then(Option::as_ref),
+            from_name: request.from_name.as_ref().and_then(Option::as_ref),
+        },
+        &GenerateOptions {
+            search_provider: request.search_provider.clone().flatten(),
+            llm_provider: request.llm_provider.clone().flatten(),
+            model: request.model.clone().flatten(),
+            max_queries: request.max_queries.flatten(),
+            failure_footer: request.failure_footer.flatten(),
+            fallbacks: request.fallbacks.clone(),
+        },
+        &providers,
+    ) {
+        return HttpResponse::BadRequest().body(message);
+    }
+
+    match digest_store.update(&id, request) {
+        Ok(Some(digest)) => HttpResponse::Ok().json(digest),
+        Ok(None) => HttpResponse::NotFound().body("Digest not found"),
+        Err(e) => {
+            log_error(&format!("Failed to save digest: {}", e));
+            HttpResponse::InternalServerError().body(format!("Failed to save digest: {}", e))
+        }
+    }
+}
+
+pub async fn delete_digest(
+    digest_store: web::Data<DigestStore>,
+    id: web::Path<String>,
+) -> HttpResponse {
+    match digest_store.delete(&id) {
+        Ok(true) => HttpResponse::NoContent().finish(),
+        Ok(false) => HttpResponse::NotFound().body("Digest not found"),
+        Err(e) => {
+            log_error(&format!("Failed to delete digest: {}", e));
+            HttpResponse::InternalServerError().body(format!("Failed to delete digest: {}", e))
+        }
+    }
+}
+
+fn validate_fields(
+    topic: Option<&String>,
+    subject: Option<&String>,
+    schedule: Option<&Schedule>,
+    addressing: Addressing,
+    options: &GenerateOptions,
+    providers: &Providers,
+) -> Result<(), String> {
+    for (name, value) in [("topic", topic), ("subject", subject)] {
+        if value.is_some_and(|value| value.trim().is_empty()) {
+            return Err(format!("{} must not be empty", name));
+        }
+    }
+    if let Some(schedule) = schedule {
+        validate_schedule(schedule)?;
+    }
+    validate_addressing(&addressing)?;
+    validate_options(options, providers)
+}
+
+/// The email fields of a digest that are being set.
+#[derive(Default)]
+struct Addressing<'a> {
+    send_to: Option<&'a Recipients>,
+    cc: Option<&'a Recipients>,
+    bcc: Option<&'a Recipients>,
+    reply_to: Option<&'a String>,
+    from_name: Option<&'a String>,
+}
+
+/// Checks every address parses and that `send_to` isn't empty.
+fn validate_addressing(addressing: &Addressing) -> Result<(), String> {
+    if addressing
+        .send_to
+        .is_some_and(|send_to| send_to.0.iter().all(|to| to.trim().is_empty()))
+    {
+        return Err("send_to must not be empty".to_string());
+    }
+    let reply_to = addressing
+        .reply_to
+        .map(|reply_to| Recipients(vec![reply_to.clone()]));
+    for (name, recipients) in [
+        ("send_to", addressing.send_to),
+        ("cc", addressing.cc),
+        ("bcc", addressing.bcc),
+        ("reply_to", reply_to.as_ref()),
+    ] {
+        if let Some(recipients) = recipients {
+            parse_recipients(name, recipients).map_err(|e| e.to_string())?;
+        }
+    }
+    if addressing
+        .from_name
+        .is_some_and(|name| name.contains(['\r', '\n']))
+    {
+        return Err("from_name must not contain line breaks".to_string());
+    }
+    Ok(())
+}
+
+/// Checks that every provider in the chain is registered and the query cap is in range.
+fn validate_options(options: &GenerateOptions, providers: &Providers) -> Result<(), String> {
+    for route in provider_chain(options) {
+        providers.search(route.search_provider.as_deref())?;
+        providers.llm(route.llm_provider.as_deref())?;
+    }
+    if let Some(max_queries) = options.max_queries {
+        if !(1..=MAX_QUERIES_LIMIT).contains(&max_queries) {
+            return Err(format!(
+                "max_queries must be between 1 and {}",
+                MAX_QUERIES_LIMIT
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub async fn trigger_digest(
+    digest_store: web::Data<DigestStore>,
+    run_store: web::Data<RunStore>,
+    job_store: web::Data<JobStore>,
+    providers: web::Data<Providers>,
+    mailer: web::Data<Mailer>,
+    id: web::Path<String>,
+    body: web::Bytes,
+) -> HttpResponse {
+    let options: RunOptions = match optional_body(&body, "run options") {
+        Ok(options) => options,
+        Err(response) => return response,
+    };
+    let digest = match digest_store.get(&id) {
+        Some(digest) => digest,
+        None => return HttpResponse::NotFound().body("Digest not found"),
+    };
+    if let Err(e) = validate_addressing(&Addressing {
+        send_to: options.send_to.as_ref(),
+        ..Addressing::default()
+    }) {
+        return HttpResponse::BadRequest().body(e);
+    }
+    let generate = digest_options(&digest, &options.generate);
+    if let Err(e) = validate_options(&generate, &providers) {
+        return HttpResponse::BadRequest().body(e);
+    }
+
+    let digests = vec![(digest, None)];
+    let job = job_store.create(JobTrigger::Manual, &digests);
+    let job_id = job.id.clone();
+    spawn(async move {
+        run_digests(
+            &job_id, digests, &options, &run_store, &job_store, &providers, &mailer,
+        )
+        .await;
+    });
+    HttpResponse::Accepted().json(job)
+}
+
+pub async fn preview_digest(
+    digest_store: web::Data<DigestStore>,
+    providers: web::Data<Providers>,
+    id: web::Path<String>,
+    body: web::Bytes,
+) -> HttpResponse {
+    let options: GenerateOptions = match optional_body(&body, "preview options") {
+        Ok(options) => options,
+        Err(response) => return response,
+    };
+    let digest = match digest_store.get(&id) {
+        Some(digest) => digest,
+        None => return HttpResponse::NotFound().body("Digest not found"),
+    };
+    let options = digest_options(&digest, &options);
+    preview(&digest.topic, &options, &providers).await
+}
+
+/// Parses the overrides sent to an endpoint that also takes no body, or an empty one, to mean
+/// the defaults. A body that isn't valid is refused rather than silently ignored.
+fn optional_body<T: DeserializeOwned + Default>(
+    body: &web::Bytes,
+    name: &str,
+) -> Result<T, HttpResponse> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(T::default());
+    }
+    serde_json::from_slice(body)
+        .map_err(|e| HttpResponse::BadRequest().body(format!("Invalid {}: {}", name, e)))
+}
+
+pub async fn preview_topic(
+    providers: web::Data<Providers>,
+    request: web::Json<PreviewRequest>,
+) -> HttpResponse {
+    if request.topic.trim().is_empty() {
+        return HttpResponse::BadRequest().body("topic must not be empty");
+    }
+    preview(&request.topic, &request.options, &providers).await
+}
+
+async fn preview(topic: &str, options: &GenerateOptions, providers: &Providers) -> HttpResponse {
+    if let Err(e) = validate_options(options, providers) {
+        return HttpResponse::BadRequest().body(e);
+    }
+    match generate_digest(topic, options, providers, &DigestReporter::detached()).await {
+        Ok(artifacts) => HttpResponse::Ok().json(artifacts),
+        Err(e) => e.error_response(),
+    }
+}
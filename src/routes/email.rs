@@ -1,49 +1,17 @@
-use crate::constants::utility::{log_error, log_query};
-use actix_web::{web, Error};
-use dotenv::dotenv;
-use lettre::message::{MultiPart, SinglePart};
-use lettre::transport::smtp::authentication::Credentials;
-use lettre::transport::smtp::SmtpTransport;
-use lettre::{Message, Transport};
-use serde::Deserialize;
-use std::time::Instant;
-
-#[derive(Deserialize)]
-pub struct Email {
-    pub email: String,
-    pub subject: String,
-    pub body: String,
-}
-
-pub async fn send_email(info: web::Json<Email>) -> Result<String, Error> {
-    dotenv().ok();
-
-    let start_time: Instant = Instant::now();
-
-    let smtp_host = std::env::var("SMTP_HOST").expect("SMTP_HOST must be set");
-    let smtp_username = std::env::var("SMTP_USERNAME").expect("SMTP_USERNAME must be set");
-    let smtp_password = std::env::var("SMTP_PASSWORD").expect("SMTP_PASSWORD must be set");
-
-    let email = Message::builder()
-        .from(smtp_username.parse().unwrap())
-        .to(info.email.parse().unwrap())
-        .subject(&info.subject)
-        .multipart(MultiPart::alternative().singlepart(SinglePart::html(info.body.clone())))
-        .unwrap();
-
-    let creds = Credentials::new(smtp_username.to_string(), smtp_password.to_string());
-    let mailer = SmtpTransport::relay(&smtp_host)
-        .unwrap()
-        .credentials(creds)
-        .build();
-    match mailer.send(&email) {
-        Ok(_) => log_query(&format!("Email sent successfully!")),
-        Err(e) => log_error(&format!("Could not send email: {:?}", e)),
+use crate::{
+    models::email_models::Email,
+    services::{self, email::Mailer},
+};
+use actix_web::{web, HttpResponse, ResponseError};
+
+/// Responds with the message IDs, or a JSON error: `400` for an invalid field, `500` when
+/// SMTP isn't configured and `502` when the SMTP server couldn't be reached or refused the
+/// email. When sending individually and only some copies were accepted it's a `207` listing
+/// the recipients that failed.
+pub async fn send_email(mailer: web::Data<Mailer>, info: web::Json<Email>) -> HttpResponse {
+    match services::email::send_email(&mailer, &info).await {
+        Ok(sent) if !sent.failed.is_empty() => HttpResponse::MultiStatus().json(sent),
+        Ok(sent) => HttpResponse::Ok().json(sent),
+        Err(e) => HttpResponse::build(e.status_code()).json(e),
     }
-
-    let end_time: Instant = Instant::now();
-    let duration: std::time::Duration = end_time.duration_since(start_time);
-    log_query(&format!("Email request took: {:?}", duration));
-
-    Ok(format!("Email sent!"))
 }
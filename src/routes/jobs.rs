@@ -0,0 +1,13 @@
+use crate::storage::job_store::JobStore;
+use actix_web::{web, HttpResponse};
+
+pub async fn list_jobs(job_store: web::Data<JobStore>) -> HttpResponse {
+    HttpResponse::Ok().json(job_store.list())
+}
+
+pub async fn get_job(job_store: web::Data<JobStore>, id: web::Path<String>) -> HttpResponse {
+    match job_store.get(&id) {
+        Some(job) => HttpResponse::Ok().json(job),
+        None => HttpResponse::NotFound().body("Job not found"),
+    }
+}
@@ -1,5 +1,8 @@
-pub mod bing;
+pub mod digests;
 pub mod email;
+pub mod jobs;
 pub mod notification;
 pub mod open_ai;
 pub mod perplexity;
+pub mod providers;
+pub mod search;
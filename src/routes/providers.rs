@@ -0,0 +1,6 @@
+use crate::providers::Providers;
+use actix_web::{web, HttpResponse};
+
+pub async fn provider_status(providers: web::Data<Providers>) -> HttpResponse {
+    HttpResponse::Ok().json(providers.circuits())
+}
@@ -0,0 +1,14 @@
+use crate::{
+    models::search_models::SearchQuery, providers::Providers, services::search::web_search,
+};
+use actix_web::{web, HttpResponse, ResponseError};
+
+pub async fn search(
+    providers: web::Data<Providers>,
+    request: web::Json<SearchQuery>,
+) -> HttpResponse {
+    match web_search(&providers, request.provider.as_deref(), &request.request).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => e.error_response(),
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{
+    constants::utility::log_error,
+    models::{
+        digest_models::{Digest, DigestRunState, RunOptions},
+        job_models::{DigestStage, JobTrigger},
+    },
+    providers::Providers,
+    scheduler::schedule::latest_occurrence,
+    services::{digest::run_digest, email::Mailer},
+    storage::{
+        digest_store::DigestStore,
+        job_store::{DigestReporter, JobStore},
+        run_store::RunStore,
+    },
+};
+use actix_web::{rt::spawn, web};
+use chrono::{DateTime, Duration, Utc};
+use tokio::time::{interval, MissedTickBehavior};
+
+pub const DEFAULT_TICK_SECONDS: u64 = 300;
+/// How long to wait before retrying a digest whose last scheduled run failed.
+pub const DEFAULT_RETRY_MINUTES: i64 = 30;
+
+/// Runs due digests on every tick. The first tick fires immediately, so anything missed
+/// while the machine was stopped is caught up on boot.
+pub async fn start(
+    digest_store: web::Data<DigestStore>,
+    run_store: web::Data<RunStore>,
+    job_store: web::Data<JobStore>,
+    providers: web::Data<Providers>,
+    mailer: web::Data<Mailer>,
+) {
+    let tick_seconds = std::env::var("SCHEDULER_TICK_SECONDS")
+        .ok()
+        .and_then(|seconds| seconds.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_TICK_SECONDS);
+    println!("Scheduler running every {}s", tick_seconds);
+
+    let mut ticker = interval(std::time::Duration::from_secs(tick_seconds));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    loop {
+        ticker.tick().await;
+        let due = due_digests(&digest_store, &run_store, Utc::now(), false);
+        if due.is_empty() {
+            continue;
+        }
+        let due = with_occurrences(&due);
+        let job = job_store.create(JobTrigger::Scheduler, &due);
+        run_digests(
+            &job.id,
+            due,
+            &RunOptions::default(),
+            &run_store,
+            &job_store,
+            &providers,
+            &mailer,
+        )
+        .await;
+    }
+}
+
+/// Every digest with a scheduled occurrence since its last successful run, paired with the
+/// latest such occurrence. Digests that failed recently are left until the retry delay passes.
+/// `force` ignores run history and returns every digest's most recent occurrence.
+pub fn due_digests(
+    digest_store: &DigestStore,
+    run_store: &RunStore,
+    now: DateTime<Utc>,
+    force: bool,
+) -> Vec<(Digest, DateTime<Utc>)> {
+    let retry_minutes = std::env::var("SCHEDULER_RETRY_MINUTES")
+        .ok()
+        .and_then(|minutes| minutes.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_RETRY_MINUTES);
+
+    digest_store
+        .list()
+        .into_iter()
+        .filter_map(|digest| {
+            if force {
+                return latest_occurrence(
+                    &digest.schedule,
+                    digest.timezone,
+                    digest.created_at,
+                    now,
+                )
+                .map(|occurrence| (digest, occurrence));
+            }
+            let state = run_store.get(&digest.id).unwrap_or_default();
+            if state
+                .last_failure_at
+                .is_some_and(|failed_at| now - failed_at < Duration::minutes(retry_minutes))
+            {
+                return None;
+            }
+            due_occurrence(&digest, &state, now).map(|occurrence| (digest, occurrence))
+        })
+        .collect()
+}
+
+/// Runs each digest in turn, reporting progress to the job and recording the outcome.
+/// Digests refused by the run-once guard are marked as skipped unless `options.force` is set.
+/// Digests without an occurrence are on-demand runs that don't affect the schedule.
+pub async fn run_digests(
+    job_id: &str,
+    digests: Vec<(Digest, Option<DateTime<Utc>>)>,
+    options: &RunOptions,
+    run_store: &RunStore,
+    job_store: &web::Data<JobStore>,
+    providers: &web::Data<Providers>,
+    mailer: &web::Data<Mailer>,
+) {
+    job_store.start(job_id);
+    for (digest, occurrence) in digests {
+        let digest_id = digest.id.clone();
+        let reporter = DigestReporter::new(job_store.clone(), job_id, &digest_id);
+        let token = match run_store.claim(&digest_id, occurrence, options.force) {
+            Ok(token) => token,
+            Err(reason) => {
+                println!("Skipping digest {}: {}", digest_id, reason);
+                reporter.update(|progress| {
+                    progress.stage = DigestStage::Skipped;
+                    progress.error = Some(reason);
+                });
+                continue;
+            }
+        };
+        let task_reporter = reporter.clone();
+        let task_options = options.clone();
+        let task_providers = providers.clone();
+        let task_mailer = mailer.clone();
+
+        // Run in its own task so a panic in the pipeline is recorded as a failure.
+        let run = spawn(async move {
+            run_digest(
+                &digest,
+                &task_options,
+                &task_providers,
+                &task_mailer,
+                &task_reporter,
+                Utc::now(),
+            )
+            .await
+            .map_err(|e| e.to_string())
+        })
+        .await;
+        let result = run.unwrap_or_else(|e| Err(format!("Digest run panicked: {}", e)));
+
+        let finished_at = Utc::now();
+        reporter.update(|progress| {
+            progress.finished_at = Some(finished_at);
+            progress.duration_ms = progress
+                .started_at
+                .map(|started_at| (finished_at - started_at).num_milliseconds());
+            match &result {
+                Ok(_) => progress.stage = DigestStage::Emailed,
+                Err(e) => {
+                    progress.stage = DigestStage::Failed;
+                    progress.error = Some(e.clone());
+                }
+            }
+        });
+
+        let saved = match result {
+            Ok(sent) => {
+                run_store.record_success(&digest_id, &token, occurrence, &sent, finished_at)
+            }
+            Err(e) => {
+                log_error(&format!("Digest {} failed: {}", digest_id, e));
+                run_store.record_failure(&digest_id, &token, occurrence, &e, finished_at)
+            }
+        };
+        if let Err(e) = saved {
+            log_error(&format!("Failed to save run state: {}", e));
+        }
+    }
+    job_store.finish(job_id);
+}
+
+/// The latest scheduled occurrence not yet covered by a successful run.
+pub fn due_occurrence(
+    digest: &Digest,
+    state: &DigestRunState,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let since = state
+        .last_occurrence
+        .map_or(digest.created_at, |occurrence| {
+            occurrence.max(digest.created_at)
+        });
+    latest_occurrence(&digest.schedule, digest.timezone, since, now)
+}
+
+pub fn with_occurrences(due: &[(Digest, DateTime<Utc>)]) -> Vec<(Digest, Option<DateTime<Utc>>)> {
+    due.iter()
+        .map(|(digest, occurrence)| (digest.clone(), Some(*occurrence)))
+        .collect()
+}
@@ -0,0 +1,317 @@
+use crate::models::digest_models::{Schedule, WeekOfMonth};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use std::str::FromStr;
+
+/// Upper bound on occurrences walked within a single search window.
+const MAX_OCCURRENCES_SCANNED: usize = 10_000;
+
+pub fn validate_schedule(schedule: &Schedule) -> Result<(), String> {
+    match schedule {
+        Schedule::Cron { expression } => cron::Schedule::from_str(expression)
+            .map(|_| ())
+            .map_err(|e| format!("Invalid cron expression '{}': {}", expression, e)),
+        Schedule::Weekly { days, .. } if days.is_empty() => {
+            Err("Weekly schedules need at least one day".to_string())
+        }
+        Schedule::Interval { every_minutes, .. } if *every_minutes == 0 => {
+            Err("every_minutes must be greater than 0".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The first occurrence strictly after `after`.
+pub fn next_occurrence(
+    schedule: &Schedule,
+    timezone: Tz,
+    after: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let local_date = after.with_timezone(&timezone).date_naive();
+    match schedule {
+        Schedule::Cron { expression } => {
+            // Walked in local time and resolved like the other schedules, rather than letting
+            // the cron crate drop times in a daylight saving gap.
+            let schedule = cron::Schedule::from_str(expression).ok()?;
+            let local_after = Utc.from_utc_datetime(&after.with_timezone(&timezone).naive_local());
+            let mut first_local = None;
+            let mut next: Option<DateTime<Utc>> = None;
+            for local in schedule
+                .after(&local_after)
+                .take(MAX_OCCURRENCES_SCANNED)
+                .map(|local| local.naive_utc())
+            {
+                // A time moved out of a gap can land after later local times.
+                if first_local.is_some_and(|first| local > first + Duration::hours(1)) {
+                    break;
+                }
+                if let Some(occurrence) = to_utc(timezone, local).filter(|o| *o > after) {
+                    first_local.get_or_insert(local);
+                    next = Some(next.map_or(occurrence, |next| next.min(occurrence)));
+                }
+            }
+            next
+        }
+        Schedule::Weekly { days, at } => (0..=7)
+            .map(|offset| local_date + Duration::days(offset))
+            .filter(|date| days.contains(&date.weekday()))
+            .filter_map(|date| to_utc(timezone, date.and_time(*at)))
+            .find(|occurrence| *occurrence > after),
+        Schedule::Monthly { week, day, at } => {
+            let (mut year, mut month) = (local_date.year(), local_date.month());
+            for _ in 0..=12 {
+                let occurrence = weekday_of_month(year, month, *week, *day)
+                    .and_then(|date| to_utc(timezone, date.and_time(*at)));
+                if let Some(occurrence) = occurrence.filter(|occurrence| *occurrence > after) {
+                    return Some(occurrence);
+                }
+                (year, month) = if month == 12 {
+                    (year + 1, 1)
+                } else {
+                    (year, month + 1)
+                };
+            }
+            None
+        }
+        Schedule::Interval {
+            every_minutes,
+            starting,
+        } => {
+            let start = to_utc(timezone, *starting)?;
+            if after < start {
+                return Some(start);
+            }
+            let every = i64::from(*every_minutes);
+            let elapsed = (after - start).num_minutes() / every + 1;
+            Some(start + Duration::minutes(elapsed * every))
+        }
+    }
+}
+
+/// The most recent occurrence in `(since, until]`, if any. Searches backwards from `until`
+/// in doubling windows so long gaps don't mean walking every occurrence since `since`.
+pub fn latest_occurrence(
+    schedule: &Schedule,
+    timezone: Tz,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let mut lookback = Duration::days(1);
+    loop {
+        let window_start = (until - lookback).max(since);
+        let mut latest = None;
+        let mut cursor = window_start;
+        for _ in 0..MAX_OCCURRENCES_SCANNED {
+            match next_occurrence(schedule, timezone, cursor) {
+                Some(occurrence) if occurrence <= until => {
+                    latest = Some(occurrence);
+                    cursor = occurrence;
+                }
+                _ => break,
+            }
+        }
+        if latest.is_some() || window_start == since {
+            return latest;
+        }
+        lookback = lookback * 2;
+    }
+}
+
+/// Resolves a local time for every kind of schedule. A time that falls in a daylight saving
+/// gap is moved forward an hour, so it's late rather than skipped, and a time that happens
+/// twice when the clocks go back is the first of the two, so it's sent once.
+fn to_utc(timezone: Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    timezone
+        .from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            timezone
+                .from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|occurrence| occurrence.with_timezone(&Utc))
+}
+
+fn weekday_of_month(year: i32, month: u32, week: WeekOfMonth, day: Weekday) -> Option<NaiveDate> {
+    match week {
+        WeekOfMonth::First => NaiveDate::from_weekday_of_month_opt(year, month, day, 1),
+        WeekOfMonth::Second => NaiveDate::from_weekday_of_month_opt(year, month, day, 2),
+        WeekOfMonth::Third => NaiveDate::from_weekday_of_month_opt(year, month, day, 3),
+        WeekOfMonth::Fourth => NaiveDate::from_weekday_of_month_opt(year, month, day, 4),
+        WeekOfMonth::Last => NaiveDate::from_weekday_of_month_opt(year, month, day, 5)
+            .or_else(|| NaiveDate::from_weekday_of_month_opt(year, month, day, 4)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+    use chrono_tz::Australia::Sydney;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn cron(expression: &str) -> Schedule {
+        Schedule::Cron {
+            expression: expression.to_string(),
+        }
+    }
+
+    fn weekly(days: &[Weekday], at: &str) -> Schedule {
+        Schedule::Weekly {
+            days: days.to_vec(),
+            at: NaiveTime::parse_from_str(at, "%H:%M").unwrap(),
+        }
+    }
+
+    #[test]
+    fn first_monday_of_the_month_at_7am_in_sydney() {
+        let after = utc("2026-10-18T00:00:00Z");
+        let monthly = Schedule::Monthly {
+            week: WeekOfMonth::First,
+            day: Weekday::Mon,
+            at: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+        };
+        // 7am AEDT on Monday 2 November.
+        let expected = Some(utc("2026-11-01T20:00:00Z"));
+        assert_eq!(next_occurrence(&monthly, Sydney, after), expected);
+        assert_eq!(
+            next_occurrence(&cron("0 0 7 1-7 * Mon"), Sydney, after),
+            expected
+        );
+    }
+
+    #[test]
+    fn next_occurrence_is_strictly_after() {
+        let schedule = weekly(&[Weekday::Mon, Weekday::Thu], "07:00");
+        let monday = utc("2026-10-18T20:00:00Z");
+        assert_eq!(
+            next_occurrence(&schedule, Sydney, monday - Duration::seconds(1)),
+            Some(monday)
+        );
+        assert_eq!(
+            next_occurrence(&schedule, Sydney, monday),
+            Some(utc("2026-10-21T20:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn interval_counts_from_its_start() {
+        let schedule = Schedule::Interval {
+            every_minutes: 720,
+            starting: "2026-10-10T07:00:00".parse().unwrap(),
+        };
+        let start = utc("2026-10-09T20:00:00Z");
+        assert_eq!(
+            next_occurrence(&schedule, Sydney, start - Duration::days(3)),
+            Some(start)
+        );
+        assert_eq!(
+            next_occurrence(&schedule, Sydney, utc("2026-10-12T10:00:00Z")),
+            Some(utc("2026-10-12T20:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn latest_occurrence_searches_back_past_a_long_gap() {
+        let schedule = cron("0 0 7 1 1 *");
+        let until = utc("2026-10-18T00:00:00Z");
+        // 7am AEDT on 1 January, nine months before `until`.
+        assert_eq!(
+            latest_occurrence(&schedule, Sydney, utc("2020-01-01T00:00:00Z"), until),
+            Some(utc("2025-12-31T20:00:00Z"))
+        );
+        assert_eq!(
+            latest_occurrence(&schedule, Sydney, utc("2026-01-01T00:00:00Z"), until),
+            None
+        );
+    }
+
+    #[test]
+    fn latest_occurrence_excludes_since_and_includes_until() {
+        let schedule = weekly(&[Weekday::Mon], "07:00");
+        let monday = utc("2026-10-18T20:00:00Z");
+        assert_eq!(
+            latest_occurrence(&schedule, Sydney, monday, monday + Duration::days(6)),
+            None
+        );
+        assert_eq!(
+            latest_occurrence(&schedule, Sydney, monday - Duration::days(1), monday),
+            Some(monday)
+        );
+    }
+
+    #[test]
+    fn times_in_a_daylight_saving_gap_move_forward_an_hour() {
+        // Sydney skips from 2am to 3am on Sunday 4 October 2026, so 2:30am is sent at 3:30am
+        // AEDT, whatever the kind of schedule.
+        let after = utc("2026-10-03T12:00:00Z");
+        let moved = utc("2026-10-03T16:30:00Z");
+        let expected = Some(moved);
+        assert_eq!(
+            next_occurrence(&cron("0 30 2 * * *"), Sydney, after),
+            expected
+        );
+        assert_eq!(
+            next_occurrence(&weekly(&[Weekday::Sun], "02:30"), Sydney, after),
+            expected
+        );
+        let interval = Schedule::Interval {
+            every_minutes: 1440,
+            starting: "2026-10-04T02:30:00".parse().unwrap(),
+        };
+        assert_eq!(next_occurrence(&interval, Sydney, after), expected);
+        // The next day is back to 2:30am.
+        assert_eq!(
+            next_occurrence(&cron("0 30 2 * * *"), Sydney, moved),
+            Some(utc("2026-10-04T15:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn cron_times_in_a_gap_stay_in_order() {
+        // Every 20 minutes: 2:00, 2:20 and 2:40 don't exist on 4 October and move to 3:00,
+        // 3:20 and 3:40, which are already occurrences.
+        let schedule = cron("0 */20 * * * *");
+        let mut occurrences = Vec::new();
+        let mut cursor = utc("2026-10-03T14:50:00Z");
+        for _ in 0..5 {
+            cursor = next_occurrence(&schedule, Sydney, cursor).unwrap();
+            occurrences.push(cursor);
+        }
+        assert_eq!(
+            occurrences,
+            [
+                utc("2026-10-03T15:00:00Z"),
+                utc("2026-10-03T15:20:00Z"),
+                utc("2026-10-03T15:40:00Z"),
+                utc("2026-10-03T16:00:00Z"),
+                utc("2026-10-03T16:20:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_times_in_a_daylight_saving_overlap_are_sent_once() {
+        // Sydney goes back from 3am to 2am on Sunday 5 April 2026, so 2:30am happens twice:
+        // at 15:30 UTC (AEDT) and 16:30 UTC (AEST).
+        let first = utc("2026-04-04T15:30:00Z");
+        let next_day = Some(utc("2026-04-05T16:30:00Z"));
+        for schedule in [
+            cron("0 30 2 * * *"),
+            weekly(&[Weekday::Sun, Weekday::Mon], "02:30"),
+        ] {
+            assert_eq!(
+                next_occurrence(&schedule, Sydney, utc("2026-04-04T12:00:00Z")),
+                Some(first)
+            );
+            assert_eq!(next_occurrence(&schedule, Sydney, first), next_day);
+            assert_eq!(
+                latest_occurrence(&schedule, Sydney, first, utc("2026-04-04T17:00:00Z")),
+                None
+            );
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::models::digest_models::DigestItem;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use reqwest::Url;
+use sha1::{Digest, Sha1};
+
+/// Longest iCalendar content line in bytes, longer ones are folded.
+const ICS_LINE_LENGTH: usize = 75;
+
+/// The items as CSV with a `title,summary,link,deadline` header.
+pub fn items_csv(items: &[DigestItem]) -> String {
+    let mut csv = "title,summary,link,deadline\r\n".to_string();
+    for item in items {
+        let fields = [
+            item.title.as_str(),
+            item.summary.as_str(),
+            item.link.as_deref().unwrap_or_default(),
+            item.deadline.as_deref().unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// An iCalendar file with an all-day event on each item's deadline, or `None` when no item
+/// has a deadline that parses as `YYYY-MM-DD`.
+pub fn deadlines_ics(items: &[DigestItem], subject: &str, now: DateTime<Utc>) -> Option<String> {
+    let deadlines: Vec<(&DigestItem, NaiveDate)> = items
+        .iter()
+        .filter_map(|item| {
+            let deadline = item.deadline.as_deref()?.trim();
+            Some((item, NaiveDate::parse_from_str(deadline, "%Y-%m-%d").ok()?))
+        })
+        .collect();
+    if deadlines.is_empty() {
+        return None;
+    }
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//gimme_server//digest//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", ics_text(subject)),
+    ];
+    for (item, date) in deadlines {
+        let mut description = item.summary.clone();
+        if let Some(link) = &item.link {
+            description.push_str(&format!("\n{}", link));
+        }
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            // Stable across runs so calendars update the event instead of duplicating it.
+            format!("UID:{}@gimme_server", event_id(&item.title, date)),
+            format!("DTSTAMP:{}", now.format("%Y%m%dT%H%M%SZ")),
+            format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")),
+            format!(
+                "DTEND;VALUE=DATE:{}",
+                (date + Duration::days(1)).format("%Y%m%d")
+            ),
+            format!("SUMMARY:{}", ics_text(&format!("Deadline: {}", item.title))),
+            format!("DESCRIPTION:{}", ics_text(&description)),
+        ]);
+        if let Some(url) = item.link.as_deref().and_then(ics_url) {
+            lines.push(format!("URL:{}", url));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    Some(
+        lines
+            .iter()
+            .map(|line| fold_line(line) + "\r\n")
+            .collect::<String>(),
+    )
+}
+
+/// Quotes fields containing commas, quotes or line breaks, doubling any quotes.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn ics_text(text: &str) -> String {
+    text.trim()
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+/// The link for a `URL` property, which isn't escaped, so `None` unless it parses as a URL and
+/// has no control characters that could end the line and start another property.
+fn ics_url(link: &str) -> Option<Url> {
+    let link = link.trim();
+    if link.chars().any(char::is_control) {
+        return None;
+    }
+    Url::parse(link).ok()
+}
+
+/// Splits lines over `ICS_LINE_LENGTH` bytes, continuing each with a leading space.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > ICS_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+fn event_id(title: &str, date: NaiveDate) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(format!("{}|{}", title.trim().to_lowercase(), date));
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn item(title: &str, summary: &str, link: Option<&str>, deadline: Option<&str>) -> DigestItem {
+        DigestItem {
+            title: title.to_string(),
+            summary: summary.to_string(),
+            link: link.map(|link| link.to_string()),
+            deadline: deadline.map(|deadline| deadline.to_string()),
+        }
+    }
+
+    #[test]
+    fn quotes_csv_fields_that_need_it() {
+        let csv = items_csv(&[
+            item("Fund \"A\"", "Up to $50,000", None, Some("2026-11-30")),
+            item("Plain", "Two\nlines", Some("https://a.example"), None),
+        ]);
+        assert_eq!(
+            csv,
+            "title,summary,link,deadline\r\n\
+             \"Fund \"\"A\"\"\",\"Up to $50,000\",,2026-11-30\r\n\
+             Plain,\"Two\nlines\",https://a.example,\r\n"
+        );
+    }
+
+    #[test]
+    fn escapes_ics_text() {
+        assert_eq!(ics_text(" a\\b; c, d\r\ne\nf "), r"a\\b\; c\, d\ne\nf");
+    }
+
+    #[test]
+    fn folds_long_ics_lines_without_splitting_characters() {
+        let line = format!("SUMMARY:{}", "é".repeat(40));
+        let folded = fold_line(&line);
+        let parts: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(parts.len(), 2);
+        assert!(parts.iter().all(|part| part.len() <= ICS_LINE_LENGTH));
+        assert!(parts[1].starts_with(' '));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+        assert_eq!(fold_line("SUMMARY:short"), "SUMMARY:short");
+    }
+
+    #[test]
+    fn adds_an_event_for_each_deadline() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 18, 7, 0, 0).unwrap();
+        let ics = deadlines_ics(
+            &[
+                item(
+                    "Fund",
+                    "Grants; big ones",
+                    Some("https://a.example"),
+                    Some("2026-11-30"),
+                ),
+                item("No deadline", "", None, None),
+                item("Unparsed", "", None, Some("end of November")),
+            ],
+            "Language grants",
+            now,
+        )
+        .unwrap();
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+        assert!(ics.contains("\r\nDTSTAMP:20261018T070000Z\r\n"));
+        assert!(ics.contains("\r\nDTSTART;VALUE=DATE:20261130\r\nDTEND;VALUE=DATE:20261201\r\n"));
+        assert!(ics.contains(r"DESCRIPTION:Grants\; big ones\nhttps://a.example"));
+        assert!(ics.contains("\r\nURL:https://a.example/\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn leaves_out_links_that_could_inject_properties() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 18, 7, 0, 0).unwrap();
+        for link in [
+            "https://a.example/\r\nATTENDEE:mailto:someone@example.com",
+            "https://a.example/\nRRULE:FREQ=DAILY",
+            "not a url",
+        ] {
+            let ics = deadlines_ics(
+                &[item("Fund", "", Some(link), Some("2026-11-30"))],
+                "Grants",
+                now,
+            )
+            .unwrap();
+            assert!(!ics.contains("\r\nURL:"), "{}", link);
+            assert!(!ics.contains("\r\nATTENDEE") && !ics.contains("\r\nRRULE"));
+        }
+    }
+
+    #[test]
+    fn leaves_out_the_calendar_without_deadlines() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 18, 7, 0, 0).unwrap();
+        assert!(deadlines_ics(&[item("No deadline", "", None, None)], "Grants", now).is_none());
+    }
+}
@@ -0,0 +1,1029 @@
+use crate::{
+    constants::utility::{log_error, log_query},
+    models::{
+        digest_models::{
+            Digest, DigestArtifacts, DigestAttachment, DigestItem, DigestItems, DigestSent,
+            GenerateOptions, OptimisedQueries, OptimisedQuery, ProviderRoute, ProvidersUsed,
+            QueryResult, RunOptions,
+        },
+        email_models::{Attachment, Email, Recipients},
+        job_models::{DigestStage, QueryFailure},
+        llm_models::{LlmRequest, ResponseSchema},
+        search_models::WebSearchRequest,
+    },
+    providers::{llm::LlmProvider, search::SearchProvider, Providers},
+    services::{
+        artifacts::{deadlines_ics, items_csv},
+        email::{send_email, Mailer},
+        error::PipelineError,
+        llm::complete_with,
+        plain_text,
+        search::search_with,
+    },
+    storage::job_store::DigestReporter,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use pulldown_cmark::{html, Options, Parser};
+use serde_json::{json, Value};
+use std::{collections::HashSet, fs, path::Path, sync::Arc, time::Instant};
+
+pub const MARKDOWN_TEMPLATE: &str = include_str!("../templates/markdown_template.md");
+pub const SEARCH_OPTIMISATION_PROMPT: &str = "Optimise this natural language query into search engine queries that show the best and latest results. If the query contains more than 1 request then split it into one search query per request. Give each query a short rationale.";
+pub const DEFAULT_MAX_QUERIES: usize = 5;
+/// Upper bound for a digest's `max_queries`, each query is a search and an LLM call.
+pub const MAX_QUERIES_LIMIT: usize = 20;
+pub const ITEM_EXTRACTION_PROMPT: &str = "List every item in this digest, e.g. each grant, opportunity or article, with its title, a one sentence summary, its link and its deadline or closing date as YYYY-MM-DD. Use null for a missing link or deadline.";
+const CIRCUIT_OPEN: &str = "Skipped while the circuit is open";
+
+/// Generates and emails a single digest, regardless of its schedule. Returns the providers
+/// that ended up being used and the recipients that couldn't be reached, which don't fail
+/// the run once anyone was sent the digest. `now` dates the attachments, so a replayed run
+/// makes the same requests on any day.
+pub async fn run_digest(
+    digest: &Digest,
+    options: &RunOptions,
+    providers: &Providers,
+    mailer: &Mailer,
+    progress: &DigestReporter,
+    now: DateTime<Utc>,
+) -> Result<DigestSent, PipelineError> {
+    println!("Sending notification for: {}", digest.topic);
+    let start_time = Instant::now();
+    let generate = digest_options(digest, &options.generate);
+    let mut artifacts = generate_digest(&digest.topic, &generate, providers, progress).await?;
+    let duration = start_time.elapsed();
+    log_query(&format!("Notification took: {:?}", duration));
+
+    progress.update(|p| p.stage = DigestStage::Emailing);
+    let stage_time = Instant::now();
+    let attachments = digest_attachments(digest, &generate, providers, &mut artifacts, now).await;
+    let mut email = digest_email(digest, options, artifacts.html, artifacts.text);
+    email.attachments = attachments;
+    let sent = send_email(mailer, &email).await;
+    // A send that reached nobody fails the run, which the runner records on the job.
+    progress.update(|p| {
+        p.timings.email_ms = Some(stage_time.elapsed().as_millis());
+        if let Ok(sent) = &sent {
+            p.emailed = true;
+            p.message_ids = sent.message_ids.clone();
+            p.failed_recipients = sent.failed.clone();
+        }
+    });
+    Ok(DigestSent {
+        providers: artifacts.providers,
+        failed_recipients: sent?.failed,
+    })
+}
+
+/// The digest's email, sent only to `options.send_to` when it's set.
+fn digest_email(digest: &Digest, options: &RunOptions, html: String, text: String) -> Email {
+    let (email, cc, bcc) = match &options.send_to {
+        Some(send_to) => (
+            send_to.clone(),
+            Recipients::default(),
+            Recipients::default(),
+        ),
+        None => (
+            digest.send_to.clone(),
+            digest.cc.clone(),
+            digest.bcc.clone(),
+        ),
+    };
+    Email {
+        email,
+        cc,
+        bcc,
+        reply_to: digest.reply_to.clone(),
+        from_name: digest.from_name.clone(),
+        send_individually: digest.send_individually,
+        subject: digest.subject.clone(),
+        body: html,
+        text: Some(text),
+        attachments: Vec::new(),
+    }
+}
+
+/// Builds the files listed in the digest's `attachments`. The items for the CSV and calendar
+/// are extracted from the markdown by the LLMs in the chain. They're extras, so when the
+/// extraction fails the digest is sent without them.
+async fn digest_attachments(
+    digest: &Digest,
+    options: &GenerateOptions,
+    providers: &Providers,
+    artifacts: &mut DigestArtifacts,
+    now: DateTime<Utc>,
+) -> Vec<Attachment> {
+    let needs_items = digest
+        .attachments
+        .iter()
+        .any(|kind| matches!(kind, DigestAttachment::Csv | DigestAttachment::Ics));
+    let items = if needs_items {
+        let extracted = match resolve_routes(options, providers) {
+            Ok(routes) => extract_items(&artifacts.markdown, &routes, now)
+                .await
+                .map(|(llm_name, items)| (llm_name.to_string(), items)),
+            Err(e) => Err(e),
+        };
+        match extracted {
+            Ok((llm_name, items)) => {
+                record_used(&mut artifacts.providers.llm, &llm_name);
+                Some(items)
+            }
+            Err(e) => {
+                log_error(&format!(
+                    "Sending digest {} without its item attachments: {}",
+                    digest.id, e
+                ));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut attachments = Vec::new();
+    for (index, &kind) in digest.attachments.iter().enumerate() {
+        if digest.attachments[..index].contains(&kind) {
+            continue;
+        }
+        let (filename, content_type, content) = match kind {
+            DigestAttachment::Markdown => (
+                "digest.md",
+                "text/markdown; charset=utf-8",
+                artifacts.markdown.clone(),
+            ),
+            DigestAttachment::Csv => match &items {
+                Some(items) => ("items.csv", "text/csv; charset=utf-8", items_csv(items)),
+                None => continue,
+            },
+            DigestAttachment::Ics => {
+                match items
+                    .as_deref()
+                    .and_then(|items| deadlines_ics(items, &digest.subject, now))
+                {
+                    Some(ics) => ("deadlines.ics", "text/calendar; charset=utf-8", ics),
+                    None => continue,
+                }
+            }
+        };
+        attachments.push(Attachment {
+            filename: filename.to_string(),
+            content_type: content_type.to_string(),
+            content: STANDARD.encode(content),
+        });
+    }
+    attachments
+}
+
+/// Asks the LLMs in the chain for the items in the digest, with their links and deadlines.
+async fn extract_items<'r>(
+    markdown: &str,
+    routes: &'r [Route],
+    now: DateTime<Utc>,
+) -> Result<(&'r str, Vec<DigestItem>), PipelineError> {
+    let prompt = format!(
+        "{} Today is {}. Digest: {}",
+        ITEM_EXTRACTION_PROMPT,
+        now.format("%Y-%m-%d"),
+        markdown
+    );
+    let response_schema = ResponseSchema {
+        name: "digest_items".to_string(),
+        schema: digest_items_schema(),
+    };
+    complete_in_chain(routes, &prompt, Some(response_schema), |reply| {
+        serde_json::from_str::<DigestItems>(strip_code_fence(reply))
+            .map(|extracted| extracted.items)
+            .map_err(|e| format!("Invalid digest items: {}", e))
+    })
+    .await
+}
+
+fn digest_items_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "items": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "title": { "type": "string" },
+                        "summary": { "type": "string" },
+                        "link": { "type": ["string", "null"] },
+                        "deadline": { "type": ["string", "null"] }
+                    },
+                    "required": ["title", "summary", "link", "deadline"],
+                    "additionalProperties": false
+                }
+            }
+        },
+        "required": ["items"],
+        "additionalProperties": false
+    })
+}
+
+/// Fills in anything not overridden by `options` from the digest's own settings. The digest's
+/// model is only kept when its LLM provider isn't overridden.
+pub fn digest_options(digest: &Digest, options: &GenerateOptions) -> GenerateOptions {
+    GenerateOptions {
+        search_provider: options
+            .search_provider
+            .clone()
+            .or_else(|| digest.search_provider.clone()),
+        llm_provider: options
+            .llm_provider
+            .clone()
+            .or_else(|| digest.llm_provider.clone()),
+        model: options.model.clone().or_else(|| {
+            options
+                .llm_provider
+                .is_none()
+                .then(|| digest.model.clone())
+                .flatten()
+        }),
+        max_queries: options.max_queries.or(digest.max_queries),
+        failure_footer: options.failure_footer.or(digest.failure_footer),
+        fallbacks: options
+            .fallbacks
+            .clone()
+            .or_else(|| Some(digest.fallbacks.clone())),
+    }
+}
+
+/// The primary providers from `options` followed by its fallbacks.
+pub fn provider_chain(options: &GenerateOptions) -> Vec<ProviderRoute> {
+    let primary = ProviderRoute {
+        search_provider: options.search_provider.clone(),
+        llm_provider: options.llm_provider.clone(),
+        model: options.model.clone(),
+    };
+    std::iter::once(primary)
+        .chain(options.fallbacks.iter().flatten().cloned())
+        .collect()
+}
+
+/// A step of the fallback chain with its providers looked up.
+struct Route {
+    search_name: String,
+    search: Arc<dyn SearchProvider>,
+    llm_name: String,
+    llm: Arc<dyn LlmProvider>,
+    model: Option<String>,
+}
+
+/// Looks up every step of the chain, dropping repeats. Never empty.
+fn resolve_routes(
+    options: &GenerateOptions,
+    providers: &Providers,
+) -> Result<Vec<Route>, PipelineError> {
+    let mut seen = HashSet::new();
+    let mut routes = Vec::new();
+    for route in provider_chain(options) {
+        let (search_name, search) = providers
+            .search(route.search_provider.as_deref())
+            .map_err(PipelineError::unknown_provider)?;
+        let (llm_name, llm) = providers
+            .llm(route.llm_provider.as_deref())
+            .map_err(PipelineError::unknown_provider)?;
+        if seen.insert((search_name.clone(), llm_name.clone(), route.model.clone())) {
+            routes.push(Route {
+                search_name,
+                search,
+                llm_name,
+                llm,
+                model: route.model,
+            });
+        }
+    }
+    Ok(routes)
+}
+
+fn record_used(names: &mut Vec<String>, name: &str) {
+    if !names.iter().any(|used| used == name) {
+        names.push(name.to_string());
+    }
+}
+
+/// Runs query optimisation, search, transform and HTML conversion for a topic.
+pub async fn generate_digest(
+    topic: &str,
+    options: &GenerateOptions,
+    providers: &Providers,
+    progress: &DigestReporter,
+) -> Result<DigestArtifacts, PipelineError> {
+    let routes = resolve_routes(options, providers)?;
+    println!(
+        "Using: {}",
+        routes
+            .iter()
+            .map(|route| format!("{} & {}", route.search_name, route.llm_name))
+            .collect::<Vec<String>>()
+            .join(", then ")
+    );
+    let mut used = ProvidersUsed::default();
+    progress.update(|p| {
+        p.stage = DigestStage::GeneratingQueries;
+        p.started_at = Some(Utc::now());
+    });
+    let stage_time = Instant::now();
+    let max_queries = options.max_queries.unwrap_or(DEFAULT_MAX_QUERIES);
+    let queries = create_optimized_search_queries(topic, &routes, max_queries, &mut used).await;
+    progress.update(|p| {
+        p.providers = used.clone();
+        p.queries_generated = queries.len();
+        p.timings.queries_ms = Some(stage_time.elapsed().as_millis());
+        p.stage = DigestStage::Searching;
+    });
+
+    let stage_time = Instant::now();
+    let mut results = Vec::new();
+    let mut failures = Vec::new();
+    for OptimisedQuery { query, .. } in &queries {
+        match search_in_chain(&routes, query).await {
+            Ok(result) => {
+                record_used(&mut used.search, &result.search_provider);
+                if let Some(llm_name) = &result.llm_provider {
+                    record_used(&mut used.llm, llm_name);
+                }
+                results.push(result);
+                progress.update(|p| {
+                    p.searches_completed += 1;
+                    p.providers = used.clone();
+                });
+            }
+            Err(error) => {
+                let failure = QueryFailure {
+                    query: query.clone(),
+                    error,
+                };
+                progress.update(|p| {
+                    p.searches_failed += 1;
+                    p.failures.push(failure.clone());
+                });
+                failures.push(failure);
+            }
+        }
+    }
+    progress.update(|p| {
+        p.timings.search_ms = Some(stage_time.elapsed().as_millis());
+        p.stage = DigestStage::Transforming;
+    });
+    if results.is_empty() {
+        return Err(PipelineError::AllQueriesFailed { failures });
+    }
+
+    let combined_results = results
+        .iter()
+        .map(|result| result.content.to_string())
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let stage_time = Instant::now();
+    let (llm_name, mut markdown) = convert_to_markdown(&combined_results, &routes).await?;
+    record_used(&mut used.llm, llm_name);
+    if !failures.is_empty() && options.failure_footer.unwrap_or(true) {
+        markdown.push_str(&failure_footer(failures.len(), queries.len()));
+    }
+    let html = markdown_to_html(&markdown);
+    let text = markdown_to_text(&markdown);
+    progress.update(|p| {
+        p.providers = used.clone();
+        p.transformed = true;
+        p.timings.transform_ms = Some(stage_time.elapsed().as_millis());
+    });
+
+    log_query(&format!("Converted HTML: {:?}", html));
+    write_debug_output("converted_template.html", &html);
+
+    Ok(DigestArtifacts {
+        queries,
+        results,
+        failures,
+        providers: used,
+        markdown,
+        html,
+        text,
+    })
+}
+
+fn failure_footer(failed: usize, total: usize) -> String {
+    format!(
+        "\n\n---\n\n_Some sources were unavailable: {} of {} searches failed, so this digest may be incomplete._\n",
+        failed, total
+    )
+}
+
+/// Asks the LLMs in the chain for a structured list of search queries, keeping at most
+/// `max_queries` distinct non-empty ones. Falls back to searching for the raw topic if every
+/// LLM fails.
+async fn create_optimized_search_queries(
+    topic: &str,
+    routes: &[Route],
+    max_queries: usize,
+    used: &mut ProvidersUsed,
+) -> Vec<OptimisedQuery> {
+    let prompt = format!(
+        "{} Return at most {} queries. Query: {}",
+        SEARCH_OPTIMISATION_PROMPT, max_queries, topic
+    );
+    let response_schema = ResponseSchema {
+        name: "search_queries".to_string(),
+        schema: search_queries_schema(),
+    };
+    let optimised = complete_in_chain(routes, &prompt, Some(response_schema), |reply| {
+        let optimised = serde_json::from_str::<OptimisedQueries>(strip_code_fence(reply))
+            .map_err(|e| format!("Invalid search queries: {}", e))?;
+        let queries = dedupe_queries(optimised.queries, max_queries);
+        if queries.is_empty() {
+            Err("No search queries returned".to_string())
+        } else {
+            Ok(queries)
+        }
+    })
+    .await;
+
+    match optimised {
+        Ok((llm_name, queries)) => {
+            record_used(&mut used.llm, llm_name);
+            queries
+        }
+        Err(e) => {
+            log_error(&format!(
+                "Query optimisation failed, searching for the topic instead: {}",
+                e
+            ));
+            vec![OptimisedQuery {
+                query: topic.trim().to_string(),
+                rationale: None,
+            }]
+        }
+    }
+}
+
+fn search_queries_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "queries": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string" },
+                        "rationale": { "type": ["string", "null"] }
+                    },
+                    "required": ["query", "rationale"],
+                    "additionalProperties": false
+                }
+            }
+        },
+        "required": ["queries"],
+        "additionalProperties": false
+    })
+}
+
+/// Some local models wrap JSON in a markdown code block even when asked not to.
+fn strip_code_fence(reply: &str) -> &str {
+    let reply = reply.trim();
+    match reply.strip_prefix("```") {
+        Some(fenced) => fenced
+            .trim_start_matches("json")
+            .trim_end_matches("```")
+            .trim(),
+        None => reply,
+    }
+}
+
+/// Trims and collapses whitespace, then drops empty and case-insensitively repeated queries.
+fn dedupe_queries(queries: Vec<OptimisedQuery>, max_queries: usize) -> Vec<OptimisedQuery> {
+    let mut seen = HashSet::new();
+    queries
+        .into_iter()
+        .filter_map(|optimised| {
+            let query = optimised
+                .query
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" ");
+            let query = query.trim_matches('"').trim().to_string();
+            (!query.is_empty() && seen.insert(query.to_lowercase())).then(|| OptimisedQuery {
+                query,
+                rationale: optimised
+                    .rationale
+                    .filter(|rationale| !rationale.trim().is_empty()),
+            })
+        })
+        .take(max_queries)
+        .collect()
+}
+
+/// Searches for `query` with each step of the chain in turn until one returns content.
+async fn search_in_chain(routes: &[Route], query: &str) -> Result<QueryResult, PipelineError> {
+    let mut last_error = None;
+    for route in routes {
+        if !route.search.is_available() {
+            log_error(&format!(
+                "Skipping {}, its circuit is open",
+                route.search_name
+            ));
+            last_error = Some(PipelineError::Search {
+                provider: route.search_name.clone(),
+                message: CIRCUIT_OPEN.to_string(),
+            });
+            continue;
+        }
+        match search_and_transform(
+            (&route.search_name, route.search.as_ref()),
+            query,
+            (&route.llm_name, route.llm.as_ref()),
+            route.model.as_deref(),
+        )
+        .await
+        {
+            Ok(result) => return Ok(result),
+            Err(e) => {
+                log_error(&format!("Trying the next provider for '{}': {}", query, e));
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error
+        .unwrap_or_else(|| PipelineError::unknown_provider("No providers configured".to_string())))
+}
+
+/// Searches for `query` and returns markdown, transforming the raw results with the LLM unless
+/// the provider already summarised them. No results or an empty transform count as a failure.
+pub async fn search_and_transform(
+    (search_name, search): (&str, &dyn SearchProvider),
+    query: &str,
+    (llm_name, llm): (&str, &dyn LlmProvider),
+    model: Option<&str>,
+) -> Result<QueryResult, PipelineError> {
+    let request = WebSearchRequest {
+        query: query.to_string(),
+        no_cache: skip_cache(),
+        ..WebSearchRequest::default()
+    };
+    let response = search_with(search_name, search, &request).await?;
+    let search_error = |message: &str| PipelineError::Search {
+        provider: search_name.to_string(),
+        message: message.to_string(),
+    };
+    if let Some(answer) = response.answer {
+        return match non_empty(&answer) {
+            Ok(()) => Ok(QueryResult {
+                query: query.to_string(),
+                content: answer,
+                search_provider: search_name.to_string(),
+                llm_provider: None,
+            }),
+            Err(e) => Err(search_error(&e)),
+        };
+    }
+    if response.results.is_empty() {
+        return Err(search_error("No results returned"));
+    }
+
+    let stringified_search_results = serde_json::to_string(&response.results).unwrap_or_default();
+    let request = LlmRequest {
+        model: model.map(|model| model.to_string()),
+        system: None,
+        prompt: "Retrieve the most relevant information from the following search results and return it in markdown format. If there are no results then return nothing. Use the following markdown template".to_string() + MARKDOWN_TEMPLATE + " Input:" + &stringified_search_results,
+        response_schema: None,
+        no_cache: skip_cache(),
+    };
+    let content = complete_with(llm_name, llm, &request).await?;
+    non_empty(&content).map_err(|message| PipelineError::Llm {
+        provider: llm_name.to_string(),
+        message,
+    })?;
+    Ok(QueryResult {
+        query: query.to_string(),
+        content,
+        search_provider: search_name.to_string(),
+        llm_provider: Some(llm_name.to_string()),
+    })
+}
+
+/// Returns the LLM that converted the text along with its markdown.
+async fn convert_to_markdown<'r>(
+    markdown: &str,
+    routes: &'r [Route],
+) -> Result<(&'r str, String), PipelineError> {
+    let start_time = Instant::now();
+    let prompt = "Convert this text into markdown so it's 100% valid and using the correct markdown formatting, replace all placeholder content with the content from Input. Remove any irrelevant content. Only return the formatted markdown response with no code blocks or anything else. Example Template:".to_string() +
+        MARKDOWN_TEMPLATE +
+        " Input:" +
+        markdown;
+    let (llm_name, transformed_markdown) = complete_in_chain(routes, &prompt, None, |reply| {
+        non_empty(reply).map(|()| reply.to_string())
+    })
+    .await?;
+
+    log_query(&format!("Converted markdown: {:?}", transformed_markdown));
+    write_debug_output("converted_markdown.md", &transformed_markdown);
+    let duration = start_time.elapsed();
+    log_query(&format!("Markdown conversion took: {:?}", duration));
+    Ok((llm_name, transformed_markdown))
+}
+
+/// Tries each distinct LLM and model in the chain until one gives a reply that `parse` accepts.
+async fn complete_in_chain<'r, T>(
+    routes: &'r [Route],
+    prompt: &str,
+    response_schema: Option<ResponseSchema>,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<(&'r str, T), PipelineError> {
+    let mut tried = HashSet::new();
+    let mut last_error = None;
+    for route in routes {
+        if !tried.insert((route.llm_name.as_str(), route.model.as_deref())) {
+            continue;
+        }
+        if !route.llm.is_available() {
+            log_error(&format!("Skipping {}, its circuit is open", route.llm_name));
+            last_error = Some(PipelineError::Llm {
+                provider: route.llm_name.clone(),
+                message: CIRCUIT_OPEN.to_string(),
+            });
+            continue;
+        }
+        let request = LlmRequest {
+            model: route.model.clone(),
+            system: None,
+            prompt: prompt.to_string(),
+            response_schema: response_schema.clone(),
+            no_cache: skip_cache(),
+        };
+        let parsed = complete_with(&route.llm_name, route.llm.as_ref(), &request)
+            .await
+            .and_then(|reply| {
+                parse(&reply).map_err(|message| PipelineError::Llm {
+                    provider: route.llm_name.clone(),
+                    message,
+                })
+            });
+        match parsed {
+            Ok(parsed) => return Ok((route.llm_name.as_str(), parsed)),
+            Err(e) => {
+                log_error(&format!("Trying the next LLM: {}", e));
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error
+        .unwrap_or_else(|| PipelineError::unknown_provider("No providers configured".to_string())))
+}
+
+/// Saves an intermediate output under `DEBUG_OUTPUT_DIR` when it's set.
+/// Digests fetch fresh responses instead of reading the response cache, so a scheduled run never
+/// sends stale results, unless `CACHE_DIGESTS` is `true`. Fresh responses are still cached.
+fn skip_cache() -> bool {
+    std::env::var("CACHE_DIGESTS").unwrap_or_default() != "true"
+}
+
+fn write_debug_output(file_name: &str, contents: &str) {
+    let Some(dir) = std::env::var("DEBUG_OUTPUT_DIR")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+    else {
+        return;
+    };
+    let path = Path::new(&dir).join(file_name);
+    if let Err(e) = fs::create_dir_all(&dir).and_then(|_| fs::write(&path, contents)) {
+        log_error(&format!("Failed to write {:?}: {}", path, e));
+    }
+}
+
+fn non_empty(content: &str) -> Result<(), String> {
+    if content.trim().is_empty() {
+        Err("Returned empty content".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn markdown_to_html(markdown: &str) -> String {
+    let mut html_output = String::new();
+    html::push_html(&mut html_output, markdown_parser(markdown));
+    html_output
+}
+
+/// The `text/plain` alternative to `markdown_to_html`.
+pub fn markdown_to_text(markdown: &str) -> String {
+    plain_text::render(markdown_parser(markdown))
+}
+
+fn markdown_parser(markdown: &str) -> Parser<'_> {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+    Parser::new_ext(markdown, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::search_models::WebSearchResponse,
+        providers::{
+            cassette::{Cassette, CassetteMode},
+            http::{HttpClient, HttpSettings},
+            llm::open_ai::OpenAiCompatible,
+            search::searxng::SearxngSearch,
+        },
+        storage::response_cache::ResponseCache,
+    };
+    use chrono::{Duration, TimeZone};
+    use futures_util::future::BoxFuture;
+    use std::{collections::BTreeMap, path::PathBuf};
+
+    /// Replays `tests/cassettes/digest` through search, the LLM, rendering and email. The
+    /// fixtures' responses are hand-written, recorded from a local stand-in for SearXNG and an
+    /// OpenAI-compatible API. Replay mode saves the email to a copy of the cassette instead of
+    /// sending it. Everything is configured explicitly, so the test never sets variables that
+    /// other tests read.
+    #[actix_web::test]
+    async fn replays_a_digest_from_search_to_email() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/cassettes/digest");
+        let dir = std::env::temp_dir().join(format!("gimme-replay-{}", uuid::Uuid::new_v4()));
+        copy_dir(&fixtures, &dir);
+        let cassette = Cassette::new(CassetteMode::Replay, dir.clone());
+        let providers = replay_providers(&cassette, &dir);
+        let mailer = Mailer::unconfigured(Some(cassette));
+        let digest: Digest = serde_json::from_value(json!({
+            "id": "replay",
+            "topic": "Grants for indigenous language revitalisation",
+            "subject": "Language grants",
+            "schedule": { "kind": "cron", "expression": "0 0 7 * * Mon" },
+            "timezone": "Pacific/Auckland",
+            "send_to": "team@example.com",
+            "attachments": ["markdown", "csv", "ics"],
+            "max_queries": 1,
+            "created_at": "2026-10-01T00:00:00Z"
+        }))
+        .unwrap();
+        let now = Utc.with_ymd_and_hms(2026, 10, 18, 7, 0, 0).unwrap();
+
+        let sent = run_digest(
+            &digest,
+            &RunOptions::default(),
+            &providers,
+            &mailer,
+            &DigestReporter::detached(),
+            now,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(sent.providers.search, ["searxng"]);
+        assert_eq!(sent.providers.llm, ["openai"]);
+        assert!(sent.failed_recipients.is_empty());
+        let emails: Vec<PathBuf> = fs::read_dir(dir.join("smtp"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(emails.len(), 1);
+        let email: Value = serde_json::from_str(&fs::read_to_string(&emails[0]).unwrap()).unwrap();
+        assert_eq!(email["to"], json!(["team@example.com"]));
+        assert_eq!(email["subject"], "Language grants");
+        let html = email["html"].as_str().unwrap();
+        assert!(html.contains("<h1>Indigenous language grants</h1>"));
+        assert!(html.contains("<table>"));
+        let text = email["text"].as_str().unwrap();
+        assert!(text.contains("Fund: First Languages Fund\nCloses: 2026-11-30"));
+        assert!(text.contains("[1] https://grants.example.org/first-languages-2026"));
+        let attachment = |filename: &str| {
+            let attachment = email["attachments"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|attachment| attachment["filename"] == filename)
+                .unwrap_or_else(|| panic!("{} wasn't attached", filename));
+            let bytes = STANDARD
+                .decode(attachment["content"].as_str().unwrap())
+                .unwrap();
+            String::from_utf8(bytes).unwrap()
+        };
+        assert!(attachment("digest.md").starts_with("# Indigenous language grants"));
+        assert!(attachment("items.csv").contains(
+            "First Languages Fund 2026,\"Up to $50,000 for community-led language revitalisation projects.\",https://grants.example.org/first-languages-2026,2026-11-30"
+        ));
+        assert!(attachment("deadlines.ics").contains("DTSTART;VALUE=DATE:20261130"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Gives the same reply, or error, to every prompt.
+    struct FixedLlm(Result<&'static str, &'static str>);
+
+    impl LlmProvider for FixedLlm {
+        fn complete<'a>(
+            &'a self,
+            _request: &'a LlmRequest,
+        ) -> BoxFuture<'a, Result<String, String>> {
+            Box::pin(async move { self.0.map(str::to_string).map_err(str::to_string) })
+        }
+
+        fn default_model(&self) -> &str {
+            "fixed"
+        }
+    }
+
+    struct NoSearch;
+
+    impl SearchProvider for NoSearch {
+        fn search<'a>(
+            &'a self,
+            _request: &'a WebSearchRequest,
+        ) -> BoxFuture<'a, Result<WebSearchResponse, String>> {
+            Box::pin(async { Ok(WebSearchResponse::default()) })
+        }
+    }
+
+    fn routes(replies: &[(&str, Result<&'static str, &'static str>)]) -> Vec<Route> {
+        replies
+            .iter()
+            .map(|(name, reply)| Route {
+                search_name: "none".to_string(),
+                search: Arc::new(NoSearch),
+                llm_name: name.to_string(),
+                llm: Arc::new(FixedLlm(*reply)),
+                model: Some(name.to_string()),
+            })
+            .collect()
+    }
+
+    fn optimised(query: &str, rationale: Option<&str>) -> OptimisedQuery {
+        OptimisedQuery {
+            query: query.to_string(),
+            rationale: rationale.map(|rationale| rationale.to_string()),
+        }
+    }
+
+    fn query_strings(queries: &[OptimisedQuery]) -> Vec<&str> {
+        queries.iter().map(|query| query.query.as_str()).collect()
+    }
+
+    #[test]
+    fn strips_markdown_code_fences() {
+        let json = r#"{"queries": []}"#;
+        for reply in [
+            "{\"queries\": []}",
+            "  {\"queries\": []}\n",
+            "```json\n{\"queries\": []}\n```",
+            "```\n{\"queries\": []}\n```\n",
+        ] {
+            assert_eq!(strip_code_fence(reply), json, "{}", reply);
+        }
+    }
+
+    #[test]
+    fn drops_blank_and_repeated_queries() {
+        let queries = dedupe_queries(
+            vec![
+                optimised("  language   grants 2026 ", Some("Open rounds")),
+                optimised("", Some("Nothing")),
+                optimised("   ", None),
+                optimised("\"\"", None),
+                optimised("Language Grants 2026", Some("A repeat")),
+                optimised("\"language nests\"", Some(" ")),
+            ],
+            5,
+        );
+        assert_eq!(
+            query_strings(&queries),
+            ["language grants 2026", "language nests"]
+        );
+        assert_eq!(queries[0].rationale.as_deref(), Some("Open rounds"));
+        assert_eq!(queries[1].rationale, None);
+    }
+
+    #[test]
+    fn caps_queries_after_dropping_repeats() {
+        let queries = dedupe_queries(
+            ["a", "A", "b", "c", "d"]
+                .iter()
+                .map(|query| optimised(query, None))
+                .collect(),
+            3,
+        );
+        assert_eq!(query_strings(&queries), ["a", "b", "c"]);
+    }
+
+    #[actix_web::test]
+    async fn reads_fenced_structured_queries() {
+        let reply = "```json\n{\"queries\": [\
+            {\"query\": \"grants 2026\", \"rationale\": \"Open rounds\"},\
+            {\"query\": \"GRANTS 2026\", \"rationale\": null},\
+            {\"query\": \"language nests\", \"rationale\": null},\
+            {\"query\": \"scholarships\", \"rationale\": null}\
+        ]}\n```";
+        let mut used = ProvidersUsed::default();
+        let queries = create_optimized_search_queries(
+            "grants",
+            &routes(&[("local", Ok(reply))]),
+            2,
+            &mut used,
+        )
+        .await;
+        assert_eq!(query_strings(&queries), ["grants 2026", "language nests"]);
+        assert_eq!(used.llm, ["local"]);
+    }
+
+    #[actix_web::test]
+    async fn tries_the_next_llm_when_a_reply_is_malformed() {
+        let mut used = ProvidersUsed::default();
+        let queries = create_optimized_search_queries(
+            "grants",
+            &routes(&[
+                ("local", Ok("Here are some queries: grants 2026")),
+                (
+                    "openai",
+                    Ok(r#"{"queries": [{"query": "grants 2026", "rationale": null}]}"#),
+                ),
+            ]),
+            5,
+            &mut used,
+        )
+        .await;
+        assert_eq!(query_strings(&queries), ["grants 2026"]);
+        assert_eq!(used.llm, ["openai"]);
+    }
+
+    #[actix_web::test]
+    async fn falls_back_to_the_topic_when_no_llm_gives_queries() {
+        let mut used = ProvidersUsed::default();
+        let queries = create_optimized_search_queries(
+            "  Grants for language revitalisation ",
+            &routes(&[
+                ("local", Ok("not json")),
+                (
+                    "blank",
+                    Ok(r#"{"queries": [{"query": " ", "rationale": null}]}"#),
+                ),
+                ("openai", Err("Timed out")),
+            ]),
+            5,
+            &mut used,
+        )
+        .await;
+        assert_eq!(
+            query_strings(&queries),
+            ["Grants for language revitalisation"]
+        );
+        assert_eq!(queries[0].rationale, None);
+        assert!(used.llm.is_empty());
+    }
+
+    /// SearXNG and an OpenAI-compatible API served from `cassette`, without the response cache.
+    fn replay_providers(cassette: &Cassette, dir: &Path) -> Providers {
+        let client = |name: &str| {
+            HttpClient::new(name, HttpSettings::default(), Some(cassette.clone())).unwrap()
+        };
+        let http = BTreeMap::from([
+            ("searxng".to_string(), client("searxng")),
+            ("openai".to_string(), client("openai")),
+        ]);
+        let search: Arc<dyn SearchProvider> = Arc::new(SearxngSearch {
+            base_url: Some("http://searxng.test".to_string()),
+            authorization: None,
+            http: http["searxng"].clone(),
+        });
+        let llm: Arc<dyn LlmProvider> = Arc::new(OpenAiCompatible {
+            base_url: "http://llm.test/v1".to_string(),
+            api_key: Some("test".to_string()),
+            api_key_header: None,
+            default_model: "gpt-4o-mini".to_string(),
+            http: http["openai"].clone(),
+        });
+        let cache = ResponseCache::new(dir.join("cache"), Duration::zero(), Duration::zero());
+        Providers::new(
+            (
+                BTreeMap::from([("searxng".to_string(), search)]),
+                "searxng".to_string(),
+            ),
+            (
+                BTreeMap::from([("openai".to_string(), llm)]),
+                "openai".to_string(),
+            ),
+            http,
+            Arc::new(cache),
+        )
+        .unwrap()
+    }
+
+    fn copy_dir(from: &Path, to: &Path) {
+        fs::create_dir_all(to).unwrap();
+        for entry in fs::read_dir(from).unwrap() {
+            let entry = entry.unwrap();
+            if entry.file_type().unwrap().is_dir() {
+                copy_dir(&entry.path(), &to.join(entry.file_name()));
+            } else {
+                fs::copy(entry.path(), to.join(entry.file_name())).unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,460 @@
+use crate::{
+    constants::utility::{log_error, log_query},
+    models::email_models::{Attachment, Email, EmailSent, FailedRecipient, Recipients},
+    providers::cassette::{Cassette, CassetteMode},
+    services::error::PipelineError,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use lettre::{
+    message::{header::ContentType, Attachment as AttachmentPart, Mailbox, MultiPart, SinglePart},
+    transport::smtp::{authentication::Credentials, PoolConfig},
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use serde_json::json;
+use std::{collections::HashSet, time::Instant};
+use uuid::Uuid;
+
+/// Longest header line allowed by RFC 5322, less room for `Subject: `.
+pub const MAX_SUBJECT_LENGTH: usize = 988;
+/// Default cap on the decoded size of an email's attachments, overridden by
+/// `EMAIL_MAX_ATTACHMENT_BYTES`.
+pub const DEFAULT_MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+/// Room in a `POST /api/email` body for everything besides the base64 attachments.
+pub const REQUEST_HEADROOM_BYTES: usize = 2 * 1024 * 1024;
+
+/// How the connection to `SMTP_HOST` is secured.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SmtpTls {
+    /// TLS from the first byte, port 465 by default.
+    Implicit,
+    /// Upgraded with `STARTTLS`, port 587 by default.
+    StartTls,
+    /// Unencrypted, port 25 by default. Only for local servers like MailHog.
+    None,
+}
+
+/// The SMTP relay emails are sent through. Created once so connections are pooled.
+pub struct Mailer {
+    /// `None` when `SMTP_HOST` isn't set, so sending fails but the server still starts.
+    transport: Option<AsyncSmtpTransport<Tokio1Executor>>,
+    from: Option<Mailbox>,
+    /// Where sent emails are saved, from `CASSETTE_MODE` and `CASSETTE_DIR`.
+    cassette: Option<Cassette>,
+}
+
+impl Mailer {
+    /// Configured with `SMTP_HOST`, `SMTP_PORT`, `SMTP_TLS` (`implicit`, `starttls` or `none`,
+    /// defaults to `implicit`), `SMTP_USERNAME`, `SMTP_PASSWORD`, `SMTP_POOL_SIZE` and
+    /// `SMTP_FROM` (e.g. `Gimme Digests <bot@example.com>`, defaults to `SMTP_USERNAME`).
+    pub fn from_env() -> Result<Self, String> {
+        let cassette = Cassette::from_env()?;
+        let username = smtp_var("SMTP_USERNAME");
+        let Some(host) = smtp_var("SMTP_HOST") else {
+            return Ok(Mailer::unconfigured(cassette));
+        };
+        let from = smtp_var("SMTP_FROM")
+            .or_else(|| username.clone())
+            .map(|from| {
+                from.parse::<Mailbox>()
+                    .map_err(|e| format!("Invalid SMTP_FROM address '{}': {}", from, e))
+            })
+            .transpose()?;
+
+        let tls = match smtp_var("SMTP_TLS").as_deref().unwrap_or("implicit") {
+            "implicit" => SmtpTls::Implicit,
+            "starttls" => SmtpTls::StartTls,
+            "none" => SmtpTls::None,
+            tls => {
+                return Err(format!(
+                    "SMTP_TLS must be implicit, starttls or none, got '{}'",
+                    tls
+                ))
+            }
+        };
+        let mut builder = match tls {
+            SmtpTls::Implicit => AsyncSmtpTransport::<Tokio1Executor>::relay(&host),
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host),
+            SmtpTls::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                &host,
+            )),
+        }
+        .map_err(|e| format!("Invalid SMTP_HOST: {}", e))?;
+
+        if let Some(port) = smtp_var("SMTP_PORT") {
+            builder = builder.port(
+                port.parse()
+                    .map_err(|_| "SMTP_PORT must be a number".to_string())?,
+            );
+        }
+        if let (Some(username), Some(password)) = (username, smtp_var("SMTP_PASSWORD")) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+        let mut pool = PoolConfig::new();
+        if let Some(size) = smtp_var("SMTP_POOL_SIZE") {
+            pool = pool.max_size(
+                size.parse()
+                    .map_err(|_| "SMTP_POOL_SIZE must be a number".to_string())?,
+            );
+        }
+
+        Ok(Mailer {
+            transport: Some(builder.pool_config(pool).build()),
+            from,
+            cassette,
+        })
+    }
+
+    /// A mailer without SMTP settings, which can only save emails to a replay cassette.
+    pub fn unconfigured(cassette: Option<Cassette>) -> Self {
+        Mailer {
+            transport: None,
+            from: None,
+            cassette,
+        }
+    }
+}
+
+/// The parsed addresses of one email to send.
+pub struct Envelope {
+    pub to: Vec<Mailbox>,
+    pub cc: Vec<Mailbox>,
+    pub bcc: Vec<Mailbox>,
+    pub reply_to: Option<Mailbox>,
+}
+
+/// Checks the fields that would otherwise make the email unsendable.
+pub fn validate_email(info: &Email) -> Result<Envelope, PipelineError> {
+    let to = parse_recipients("email", &info.email)?;
+    if to.is_empty() {
+        return Err(invalid_email(
+            "email",
+            "must have at least one address".to_string(),
+        ));
+    }
+    let cc = parse_recipients("cc", &info.cc)?;
+    let bcc = parse_recipients("bcc", &info.bcc)?;
+    let reply_to = info
+        .reply_to
+        .as_deref()
+        .map(|address| parse_mailbox("reply_to", address))
+        .transpose()?;
+    if info
+        .from_name
+        .as_ref()
+        .is_some_and(|name| name.contains(['\r', '\n']))
+    {
+        return Err(invalid_email(
+            "from_name",
+            "must not contain line breaks".to_string(),
+        ));
+    }
+    if info.subject.trim().is_empty() {
+        return Err(invalid_email("subject", "must not be empty".to_string()));
+    }
+    if info.subject.contains(['\r', '\n']) {
+        return Err(invalid_email(
+            "subject",
+            "must not contain line breaks".to_string(),
+        ));
+    }
+    if info.subject.chars().count() > MAX_SUBJECT_LENGTH {
+        return Err(invalid_email(
+            "subject",
+            format!("must be at most {} characters", MAX_SUBJECT_LENGTH),
+        ));
+    }
+    Ok(Envelope {
+        to,
+        cc,
+        bcc,
+        reply_to,
+    })
+}
+
+/// Decodes the attachments, checking their filenames and MIME types and that together they
+/// fit in `EMAIL_MAX_ATTACHMENT_BYTES`.
+pub fn decode_attachments(attachments: &[Attachment]) -> Result<Vec<SinglePart>, PipelineError> {
+    let max_bytes = max_attachment_bytes();
+    let mut total = 0;
+    let mut parts = Vec::new();
+    for attachment in attachments {
+        let filename = attachment.filename.trim();
+        if filename.is_empty() || filename.contains(['\r', '\n', '/', '\\']) {
+            return Err(invalid_email(
+                "attachments",
+                format!("'{}' is not a valid filename", attachment.filename),
+            ));
+        }
+        let content_type = ContentType::parse(attachment.content_type.trim()).map_err(|_| {
+            invalid_email(
+                "attachments",
+                format!(
+                    "'{}' is not a valid MIME type for {}",
+                    attachment.content_type, filename
+                ),
+            )
+        })?;
+        let content: String = attachment
+            .content
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect();
+        let bytes = STANDARD.decode(content).map_err(|e| {
+            invalid_email(
+                "attachments",
+                format!("{} is not valid base64: {}", filename, e),
+            )
+        })?;
+        total += bytes.len();
+        if total > max_bytes {
+            return Err(invalid_email(
+                "attachments",
+                format!(
+                    "attachments are over the {} byte limit (EMAIL_MAX_ATTACHMENT_BYTES)",
+                    max_bytes
+                ),
+            ));
+        }
+        parts.push(AttachmentPart::new(filename.to_string()).body(bytes, content_type));
+    }
+    Ok(parts)
+}
+
+pub fn max_attachment_bytes() -> usize {
+    std::env::var("EMAIL_MAX_ATTACHMENT_BYTES")
+        .ok()
+        .and_then(|max| max.parse().ok())
+        .unwrap_or(DEFAULT_MAX_ATTACHMENT_BYTES)
+}
+
+/// Largest `POST /api/email` body accepted: attachments at the limit once base64 encoded,
+/// plus the headroom.
+pub fn max_request_bytes() -> usize {
+    max_attachment_bytes().div_ceil(3) * 4 + REQUEST_HEADROOM_BYTES
+}
+
+/// Parses every address in `recipients`, naming `field` in the error.
+pub fn parse_recipients(
+    field: &str,
+    recipients: &Recipients,
+) -> Result<Vec<Mailbox>, PipelineError> {
+    recipients
+        .0
+        .iter()
+        .map(|address| parse_mailbox(field, address))
+        .collect()
+}
+
+/// Sends an HTML email and returns the `Message-ID` of every message sent. With
+/// `send_individually` each recipient gets their own message, and the other messages are
+/// still sent when one fails: as long as one was accepted the result is a success listing
+/// the recipients that failed, and it's only an error when none were. With a cassette the
+/// emails are also saved, and in replay mode they're only saved.
+pub async fn send_email(mailer: &Mailer, info: &Email) -> Result<EmailSent, PipelineError> {
+    let start_time: Instant = Instant::now();
+    let envelope = validate_email(info)?;
+    let attachments = decode_attachments(&info.attachments)?;
+    let envelopes = if info.send_individually {
+        individual_envelopes(envelope)
+    } else {
+        vec![envelope]
+    };
+
+    if let Some(cassette) = mailer
+        .cassette
+        .as_ref()
+        .filter(|c| c.mode == CassetteMode::Replay)
+    {
+        let mut message_ids = Vec::new();
+        for envelope in &envelopes {
+            record_email(cassette, envelope, info);
+            message_ids.push(message_id(envelope.to[0].email.domain()));
+        }
+        log_query(&format!(
+            "Replay mode, not sending {} email(s)",
+            envelopes.len()
+        ));
+        return Ok(EmailSent {
+            message_ids,
+            failed: Vec::new(),
+        });
+    }
+
+    let transport = mailer
+        .transport
+        .as_ref()
+        .ok_or_else(|| config_error("SMTP_HOST not set".to_string()))?;
+    let from = mailer
+        .from
+        .as_ref()
+        .ok_or_else(|| config_error("SMTP_FROM or SMTP_USERNAME not set".to_string()))?;
+    let from_name = info
+        .from_name
+        .as_ref()
+        .map(|name| name.trim().to_string())
+        .or_else(|| from.name.clone())
+        .filter(|name| !name.is_empty());
+    let from = Mailbox::new(from_name, from.email.clone());
+
+    // Every message is built before any is sent, so a message that can't be built fails the
+    // request without anyone having been sent a copy.
+    let mut messages = Vec::new();
+    for envelope in &envelopes {
+        let message_id = message_id(from.email.domain());
+        let mut builder = Message::builder()
+            .message_id(Some(message_id.clone()))
+            .from(from.clone())
+            .subject(info.subject.trim());
+        for mailbox in &envelope.to {
+            builder = builder.to(mailbox.clone());
+        }
+        for mailbox in &envelope.cc {
+            builder = builder.cc(mailbox.clone());
+        }
+        for mailbox in &envelope.bcc {
+            builder = builder.bcc(mailbox.clone());
+        }
+        if let Some(reply_to) = &envelope.reply_to {
+            builder = builder.reply_to(reply_to.clone());
+        }
+        let mut body = match &info.text {
+            Some(text) => MultiPart::alternative_plain_html(text.clone(), info.body.clone()),
+            None => MultiPart::alternative().singlepart(SinglePart::html(info.body.clone())),
+        };
+        if !attachments.is_empty() {
+            body = attachments
+                .iter()
+                .fold(MultiPart::mixed().multipart(body), |mixed, attachment| {
+                    mixed.singlepart(attachment.clone())
+                });
+        }
+        let email = builder
+            .multipart(body)
+            .map_err(|e| invalid_email("email", format!("could not be built: {}", e)))?;
+        messages.push((envelope, message_id, email));
+    }
+
+    let mut message_ids = Vec::new();
+    let mut failures = Vec::new();
+    for (envelope, message_id, email) in messages {
+        match transport.send(email).await {
+            Ok(_) => {
+                message_ids.push(message_id);
+                if let Some(cassette) = &mailer.cassette {
+                    record_email(cassette, envelope, info);
+                }
+            }
+            Err(e) => failures.push(FailedRecipient {
+                recipient: addresses(&envelope.to).join(", "),
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    let duration = start_time.elapsed();
+    log_query(&format!("Email request took: {:?}", duration));
+    match failures.as_slice() {
+        [] => log_query(&format!(
+            "Sent {} email(s) successfully!",
+            message_ids.len()
+        )),
+        [failure] if envelopes.len() == 1 => return Err(email_error(failure.error.clone())),
+        _ if message_ids.is_empty() => {
+            return Err(email_error(format!(
+                "None of {} emails were sent. {}",
+                envelopes.len(),
+                describe_failures(&failures)
+            )))
+        }
+        _ => log_error(&format!(
+            "Sent {} of {} emails. {}",
+            message_ids.len(),
+            envelopes.len(),
+            describe_failures(&failures)
+        )),
+    }
+    Ok(EmailSent {
+        message_ids,
+        failed: failures,
+    })
+}
+
+fn describe_failures(failures: &[FailedRecipient]) -> String {
+    failures
+        .iter()
+        .map(|failure| format!("{}: {}", failure.recipient, failure.error))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// One envelope per distinct address in `to`, `cc` and `bcc`, each addressed only to them.
+fn individual_envelopes(envelope: Envelope) -> Vec<Envelope> {
+    let mut seen = HashSet::new();
+    envelope
+        .to
+        .into_iter()
+        .chain(envelope.cc)
+        .chain(envelope.bcc)
+        .filter(|mailbox| seen.insert(mailbox.email.clone()))
+        .map(|mailbox| Envelope {
+            to: vec![mailbox],
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            reply_to: envelope.reply_to.clone(),
+        })
+        .collect()
+}
+
+fn record_email(cassette: &Cassette, envelope: &Envelope, info: &Email) {
+    cassette.record_email(&json!({
+        "to": addresses(&envelope.to),
+        "cc": addresses(&envelope.cc),
+        "bcc": addresses(&envelope.bcc),
+        "subject": info.subject,
+        "html": info.body,
+        "text": info.text,
+        "attachments": info.attachments,
+    }));
+}
+
+fn addresses(mailboxes: &[Mailbox]) -> Vec<String> {
+    mailboxes
+        .iter()
+        .map(|mailbox| mailbox.to_string())
+        .collect()
+}
+
+fn parse_mailbox(field: &str, address: &str) -> Result<Mailbox, PipelineError> {
+    address.trim().parse::<Mailbox>().map_err(|e| {
+        invalid_email(
+            field,
+            format!("'{}' is not an email address: {}", address, e),
+        )
+    })
+}
+
+fn message_id(domain: &str) -> String {
+    format!("<{}@{}>", Uuid::new_v4(), domain)
+}
+
+fn smtp_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn invalid_email(field: &str, message: String) -> PipelineError {
+    log_error(&format!("Invalid {}: {}", field, message));
+    PipelineError::InvalidEmail {
+        field: field.to_string(),
+        message,
+    }
+}
+
+fn config_error(message: String) -> PipelineError {
+    log_error(&format!("Email isn't configured: {}", message));
+    PipelineError::EmailConfig { message }
+}
+
+fn email_error(message: String) -> PipelineError {
+    log_error(&format!("Could not send email: {}", message));
+    PipelineError::Email { message }
+}
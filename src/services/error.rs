@@ -0,0 +1,85 @@
+use crate::models::job_models::QueryFailure;
+use actix_web::{http::StatusCode, ResponseError};
+use serde::Serialize;
+use std::fmt;
+
+/// Why a search, LLM or email step failed. Failures are returned, never passed on as content.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PipelineError {
+    /// The requested provider isn't registered.
+    UnknownProvider {
+        message: String,
+    },
+    Search {
+        provider: String,
+        message: String,
+    },
+    Llm {
+        provider: String,
+        message: String,
+    },
+    /// An email field that can't be sent, e.g. a malformed address.
+    InvalidEmail {
+        field: String,
+        message: String,
+    },
+    /// The server can't send email, e.g. `SMTP_HOST` isn't set.
+    EmailConfig {
+        message: String,
+    },
+    /// Sending failed, including the SMTP server's reply when it refused the message.
+    Email {
+        message: String,
+    },
+    /// Every search query failed, so there is nothing to send.
+    AllQueriesFailed {
+        failures: Vec<QueryFailure>,
+    },
+}
+
+impl PipelineError {
+    pub fn unknown_provider(message: String) -> Self {
+        PipelineError::UnknownProvider { message }
+    }
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineError::UnknownProvider { message } => write!(f, "{}", message),
+            PipelineError::Search { provider, message } => {
+                write!(f, "Search with {} failed: {}", provider, message)
+            }
+            PipelineError::Llm { provider, message } => {
+                write!(f, "Completion with {} failed: {}", provider, message)
+            }
+            PipelineError::InvalidEmail { field, message } => {
+                write!(f, "Invalid {}: {}", field, message)
+            }
+            PipelineError::EmailConfig { message } => {
+                write!(f, "Email isn't configured: {}", message)
+            }
+            PipelineError::Email { message } => write!(f, "Could not send email: {}", message),
+            PipelineError::AllQueriesFailed { failures } => {
+                write!(f, "All {} search queries failed", failures.len())?;
+                match failures.first() {
+                    Some(failure) => write!(f, ", first: {}", failure.error),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl ResponseError for PipelineError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            PipelineError::UnknownProvider { .. } | PipelineError::InvalidEmail { .. } => {
+                StatusCode::BAD_REQUEST
+            }
+            PipelineError::EmailConfig { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_GATEWAY,
+        }
+    }
+}
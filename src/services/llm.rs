@@ -0,0 +1,32 @@
+use crate::{
+    constants::utility::log_error,
+    models::llm_models::LlmRequest,
+    providers::{llm::LlmProvider, Providers},
+    services::error::PipelineError,
+};
+
+/// Prompts a registered LLM provider, or the default one when `name` is `None`.
+pub async fn complete(
+    providers: &Providers,
+    name: Option<&str>,
+    request: &LlmRequest,
+) -> Result<String, PipelineError> {
+    let (name, provider) = providers
+        .llm(name)
+        .map_err(PipelineError::unknown_provider)?;
+    complete_with(&name, provider.as_ref(), request).await
+}
+
+pub async fn complete_with(
+    name: &str,
+    provider: &dyn LlmProvider,
+    request: &LlmRequest,
+) -> Result<String, PipelineError> {
+    provider.complete(request).await.map_err(|message| {
+        log_error(&format!("Completion with {} failed: {}", name, message));
+        PipelineError::Llm {
+            provider: name.to_string(),
+            message,
+        }
+    })
+}
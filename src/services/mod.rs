@@ -0,0 +1,7 @@
+pub mod artifacts;
+pub mod digest;
+pub mod email;
+pub mod error;
+pub mod llm;
+pub mod plain_text;
+pub mod search;
@@ -0,0 +1,338 @@
+use pulldown_cmark::{Event, HeadingLevel, Tag, TagEnd};
+
+/// Renders markdown events as plain text for the `text/plain` part of an email. Links become
+/// numbered footnotes listed at the end, and each table row becomes `Header: value` lines.
+pub fn render<'a>(events: impl Iterator<Item = Event<'a>>) -> String {
+    let mut writer = TextWriter::default();
+    for event in events {
+        writer.event(event);
+    }
+    writer.finish()
+}
+
+#[derive(Default)]
+struct TextWriter {
+    out: String,
+    /// Written at the start of every line, e.g. list indentation and `> ` for quotes.
+    indent: Vec<String>,
+    line_start: bool,
+    /// A blank line goes before the next block.
+    blank_line: bool,
+    /// Just wrote a list marker, so the item's first block continues on its line.
+    after_marker: bool,
+    /// Next number of each open list, `None` for bullet lists.
+    lists: Vec<Option<u64>>,
+    /// Where the open heading's text starts, to underline it.
+    heading: Option<usize>,
+    links: Vec<OpenLink>,
+    footnotes: Vec<String>,
+    table: Option<Table>,
+}
+
+struct OpenLink {
+    url: String,
+    text: String,
+}
+
+#[derive(Default)]
+struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+    row: Vec<String>,
+    /// The cell being written, text goes here instead of the output.
+    cell: Option<String>,
+}
+
+impl TextWriter {
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) | Event::Code(text) => self.text(&text),
+            Event::InlineMath(text) | Event::DisplayMath(text) => self.text(&text),
+            Event::SoftBreak | Event::HardBreak => self.text("\n"),
+            Event::Rule => {
+                self.start_block();
+                self.text("---");
+                self.end_block();
+            }
+            Event::TaskListMarker(checked) => self.text(if checked { "[x] " } else { "[ ] " }),
+            Event::Html(_) | Event::InlineHtml(_) | Event::FootnoteReference(_) => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.start_block(),
+            Tag::Heading { .. } => {
+                self.start_block();
+                self.heading = Some(self.out.len());
+            }
+            Tag::BlockQuote(_) => {
+                self.start_block();
+                self.indent.push("> ".to_string());
+            }
+            Tag::CodeBlock(_) => {
+                self.start_block();
+                self.indent.push("    ".to_string());
+            }
+            Tag::List(start) => {
+                self.start_block();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.end_line();
+                self.blank_line = false;
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.text(&marker);
+                self.indent.push(" ".repeat(marker.len()));
+                self.after_marker = true;
+            }
+            Tag::Table(_) => {
+                self.start_block();
+                self.table = Some(Table::default());
+            }
+            Tag::TableCell => {
+                if let Some(table) = &mut self.table {
+                    table.cell = Some(String::new());
+                }
+            }
+            Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => self.links.push(OpenLink {
+                url: dest_url.to_string(),
+                text: String::new(),
+            }),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.end_block(),
+            TagEnd::Heading(level) => {
+                if let Some(start) = self.heading.take() {
+                    let underline = match level {
+                        HeadingLevel::H1 => Some('='),
+                        HeadingLevel::H2 => Some('-'),
+                        _ => None,
+                    };
+                    if let Some(underline) = underline {
+                        let width = self.out[start..].chars().count();
+                        self.text("\n");
+                        self.text(&underline.to_string().repeat(width));
+                    }
+                }
+                self.end_block();
+            }
+            TagEnd::BlockQuote | TagEnd::CodeBlock => {
+                self.indent.pop();
+                self.end_block();
+            }
+            TagEnd::List(_) => {
+                self.lists.pop();
+                self.end_block();
+            }
+            TagEnd::Item => {
+                self.indent.pop();
+                self.after_marker = false;
+            }
+            TagEnd::TableCell => {
+                if let Some(table) = &mut self.table {
+                    let cell = table.cell.take().unwrap_or_default();
+                    table.row.push(cell.trim().to_string());
+                }
+            }
+            TagEnd::TableHead => {
+                if let Some(table) = &mut self.table {
+                    table.header = std::mem::take(&mut table.row);
+                }
+            }
+            TagEnd::TableRow => {
+                if let Some(table) = &mut self.table {
+                    let row = std::mem::take(&mut table.row);
+                    table.rows.push(row);
+                }
+            }
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    self.write_table(table);
+                }
+                self.end_block();
+            }
+            TagEnd::Link | TagEnd::Image => {
+                if let Some(link) = self.links.pop() {
+                    self.footnote(link);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Each row as `Header: value` lines, leaving out empty cells, with a blank line between
+    /// rows.
+    fn write_table(&mut self, table: Table) {
+        for (index, row) in table.rows.iter().enumerate() {
+            if index > 0 {
+                self.text("\n\n");
+            }
+            let lines: Vec<String> = row
+                .iter()
+                .enumerate()
+                .filter(|(_, cell)| !cell.is_empty())
+                .map(|(column, cell)| match table.header.get(column) {
+                    Some(header) if !header.is_empty() => format!("{}: {}", header, cell),
+                    _ => cell.clone(),
+                })
+                .collect();
+            self.text(&lines.join("\n"));
+        }
+    }
+
+    /// Adds ` [n]` after the link text, reusing the number of a repeated URL. Links that
+    /// already show their URL, in-page anchors and `mailto:` links are left as they are.
+    fn footnote(&mut self, link: OpenLink) {
+        let url = link.url.trim();
+        if url.is_empty()
+            || url.starts_with('#')
+            || url.starts_with("mailto:")
+            || link.text.trim() == url
+        {
+            return;
+        }
+        let number = match self.footnotes.iter().position(|existing| existing == url) {
+            Some(index) => index + 1,
+            None => {
+                self.footnotes.push(url.to_string());
+                self.footnotes.len()
+            }
+        };
+        self.text(&format!(" [{}]", number));
+    }
+
+    fn text(&mut self, text: &str) {
+        for link in &mut self.links {
+            link.text.push_str(text);
+        }
+        if let Some(cell) = self.table.as_mut().and_then(|table| table.cell.as_mut()) {
+            // Cells are flattened onto one line.
+            cell.push_str(&text.replace('\n', " "));
+            return;
+        }
+        for (index, line) in text.split('\n').enumerate() {
+            if index > 0 {
+                self.out.push('\n');
+                self.line_start = true;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            if self.line_start {
+                self.out.push_str(&self.indent.concat());
+                self.line_start = false;
+            }
+            self.out.push_str(line);
+            self.after_marker = false;
+        }
+    }
+
+    fn start_block(&mut self) {
+        if std::mem::take(&mut self.after_marker) {
+            return;
+        }
+        self.end_line();
+        if std::mem::take(&mut self.blank_line) && !self.out.is_empty() {
+            self.out.push_str(self.indent.concat().trim_end());
+            self.out.push('\n');
+        }
+    }
+
+    fn end_block(&mut self) {
+        self.end_line();
+        self.blank_line = true;
+    }
+
+    fn end_line(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+        self.line_start = true;
+    }
+
+    fn finish(self) -> String {
+        let mut text = self.out.trim_end().to_string();
+        if !self.footnotes.is_empty() {
+            text.push_str("\n\nLinks:\n");
+            for (index, url) in self.footnotes.iter().enumerate() {
+                text.push_str(&format!("[{}] {}\n", index + 1, url));
+            }
+        } else {
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::services::digest::markdown_to_text;
+
+    #[test]
+    fn nests_lists_under_their_item() {
+        let text = markdown_to_text("- One\n  - Nested\n  - Again\n- Two\n\n1. First\n2. Second\n");
+        assert_eq!(
+            text,
+            "- One\n  - Nested\n  - Again\n- Two\n\n1. First\n2. Second\n"
+        );
+    }
+
+    #[test]
+    fn keeps_paragraphs_of_loose_items_indented() {
+        let text = markdown_to_text("- First\n\n  More about it\n\n- Second\n");
+        assert_eq!(text, "- First\n\n  More about it\n- Second\n");
+    }
+
+    #[test]
+    fn writes_table_rows_as_header_value_lines() {
+        let text = markdown_to_text(
+            "| Fund | Closes |\n| --- | --- |\n| First Languages | 2026-11-30 |\n| Nests | |\n",
+        );
+        assert_eq!(
+            text,
+            "Fund: First Languages\nCloses: 2026-11-30\n\nFund: Nests\n"
+        );
+    }
+
+    #[test]
+    fn numbers_links_and_images_as_footnotes() {
+        let text = markdown_to_text(
+            "[Apply](https://a.example) or [apply here](https://a.example), \
+             ![Logo](https://b.example/logo.png), <https://c.example>, [top](#top) and \
+             [mail](mailto:team@example.com).",
+        );
+        assert_eq!(
+            text,
+            "Apply [1] or apply here [1], Logo [2], https://c.example, top and mail.\n\n\
+             Links:\n[1] https://a.example\n[2] https://b.example/logo.png\n"
+        );
+    }
+
+    #[test]
+    fn indents_code_blocks() {
+        let text = markdown_to_text("Run:\n\n```sh\ncargo test\ncargo build\n```\n\nDone.");
+        assert_eq!(text, "Run:\n\n    cargo test\n    cargo build\n\nDone.\n");
+    }
+
+    #[test]
+    fn underlines_headings_and_quotes_block_quotes() {
+        let text = markdown_to_text("# Title\n\n## Section\n\n> Quoted\n> text");
+        assert_eq!(
+            text,
+            "Title\n=====\n\nSection\n-------\n\n> Quoted\n> text\n"
+        );
+    }
+}
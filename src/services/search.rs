@@ -0,0 +1,32 @@
+use crate::{
+    constants::utility::log_error,
+    models::search_models::{WebSearchRequest, WebSearchResponse},
+    providers::{search::SearchProvider, Providers},
+    services::error::PipelineError,
+};
+
+/// Searches with a registered provider, or the default one when `name` is `None`.
+pub async fn web_search(
+    providers: &Providers,
+    name: Option<&str>,
+    request: &WebSearchRequest,
+) -> Result<WebSearchResponse, PipelineError> {
+    let (name, provider) = providers
+        .search(name)
+        .map_err(PipelineError::unknown_provider)?;
+    search_with(&name, provider.as_ref(), request).await
+}
+
+pub async fn search_with(
+    name: &str,
+    provider: &dyn SearchProvider,
+    request: &WebSearchRequest,
+) -> Result<WebSearchResponse, PipelineError> {
+    provider.search(request).await.map_err(|message| {
+        log_error(&format!("Search with {} failed: {}", name, message));
+        PipelineError::Search {
+            provider: name.to_string(),
+            message,
+        }
+    })
+}
@@ -0,0 +1,202 @@
+use crate::{
+    constants::config::CUSTOM_EMAILS,
+    models::{
+        digest_models::{CreateDigestRequest, Digest, Schedule, UpdateDigestRequest},
+        email_models::Recipients,
+    },
+    storage::{read_json, write_json},
+};
+use chrono::Utc;
+use serde_json::Value;
+use std::{io, path::PathBuf, sync::RwLock};
+use uuid::Uuid;
+
+/// Digest definitions persisted as a JSON file so they can be managed at runtime.
+pub struct DigestStore {
+    path: PathBuf,
+    digests: RwLock<Vec<Digest>>,
+}
+
+impl DigestStore {
+    /// Loads the store from `path`, seeding it with `CUSTOM_EMAILS` the first time it is created.
+    /// Digests saved before `created_at` existed are stamped with the load time once, and the
+    /// file rewritten so the stamp doesn't move on every boot.
+    pub fn load(path: PathBuf) -> io::Result<Self> {
+        let digests = match read_json::<Vec<Value>>(&path)? {
+            Some(stored) => {
+                let missing_created_at = stored
+                    .iter()
+                    .any(|digest| digest.get("created_at").is_none());
+                let digests: Vec<Digest> = serde_json::from_value(Value::Array(stored))
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                if missing_created_at {
+                    write_json(&path, &digests)?;
+                }
+                digests
+            }
+            None => {
+                let seeded: Vec<Digest> = CUSTOM_EMAILS
+                    .iter()
+                    .map(|email| Digest {
+                        id: Uuid::new_v4().to_string(),
+                        topic: email.topic.to_string(),
+                        subject: email.subject.to_string(),
+                        schedule: Schedule::Cron {
+                            expression: email.schedule.to_string(),
+                        },
+                        timezone: email.timezone,
+                        search_provider: None,
+                        llm_provider: None,
+                        model: None,
+                        max_queries: None,
+                        failure_footer: None,
+                        fallbacks: Vec::new(),
+                        created_at: Utc::now(),
+                        send_to: Recipients(
+                            email.send_to.iter().map(|to| to.to_string()).collect(),
+                        ),
+                        cc: Recipients::default(),
+                        bcc: Recipients::default(),
+                        reply_to: None,
+                        from_name: None,
+                        send_individually: false,
+                        attachments: Vec::new(),
+                    })
+                    .collect();
+                write_json(&path, &seeded)?;
+                seeded
+            }
+        };
+
+        Ok(DigestStore {
+            path,
+            digests: RwLock::new(digests),
+        })
+    }
+
+    pub fn list(&self) -> Vec<Digest> {
+        self.digests.read().unwrap().clone()
+    }
+
+    pub fn get(&self, id: &str) -> Option<Digest> {
+        self.digests
+            .read()
+            .unwrap()
+            .iter()
+            .find(|digest| digest.id == id)
+            .cloned()
+    }
+
+    pub fn create(&self, request: CreateDigestRequest) -> io::Result<Digest> {
+        let digest = Digest {
+            id: Uuid::new_v4().to_string(),
+            topic: request.topic,
+            subject: request.subject,
+            schedule: request.schedule,
+            timezone: request.timezone,
+            send_to: request.send_to,
+            cc: request.cc.unwrap_or_default(),
+            bcc: request.bcc.unwrap_or_default(),
+            reply_to: request.reply_to,
+            from_name: request.from_name,
+            send_individually: request.send_individually.unwrap_or_default(),
+            attachments: request.attachments.unwrap_or_default(),
+            search_provider: request.search_provider,
+            llm_provider: request.llm_provider,
+            model: request.model,
+            max_queries: request.max_queries,
+            failure_footer: request.failure_footer,
+            fallbacks: request.fallbacks.unwrap_or_default(),
+            created_at: Utc::now(),
+        };
+
+        let mut digests = self.digests.write().unwrap();
+        let mut updated = digests.clone();
+        updated.push(digest.clone());
+        write_json(&self.path, &updated)?;
+        *digests = updated;
+        Ok(digest)
+    }
+
+    pub fn update(&self, id: &str, request: UpdateDigestRequest) -> io::Result<Option<Digest>> {
+        let mut digests = self.digests.write().unwrap();
+        let mut updated = digests.clone();
+        let digest = match updated.iter_mut().find(|digest| digest.id == id) {
+            Some(digest) => digest,
+            None => return Ok(None),
+        };
+
+        if let Some(topic) = request.topic {
+            digest.topic = topic;
+        }
+        if let Some(subject) = request.subject {
+            digest.subject = subject;
+        }
+        if let Some(schedule) = request.schedule {
+            digest.schedule = schedule;
+        }
+        if let Some(timezone) = request.timezone {
+            digest.timezone = timezone;
+        }
+        if let Some(send_to) = request.send_to {
+            digest.send_to = send_to;
+        }
+        if let Some(cc) = request.cc {
+            digest.cc = cc.unwrap_or_default();
+        }
+        if let Some(bcc) = request.bcc {
+            digest.bcc = bcc.unwrap_or_default();
+        }
+        if let Some(reply_to) = request.reply_to {
+            digest.reply_to = reply_to;
+        }
+        if let Some(from_name) = request.from_name {
+            digest.from_name = from_name;
+        }
+        if let Some(send_individually) = request.send_individually {
+            digest.send_individually = send_individually;
+        }
+        if let Some(attachments) = request.attachments {
+            digest.attachments = attachments;
+        }
+        if let Some(search_provider) = request.search_provider {
+            digest.search_provider = search_provider;
+        }
+        if let Some(llm_provider) = request.llm_provider {
+            digest.llm_provider = llm_provider;
+        }
+        if let Some(model) = request.model {
+            digest.model = model;
+        }
+        if let Some(max_queries) = request.max_queries {
+            digest.max_queries = max_queries;
+        }
+        if let Some(failure_footer) = request.failure_footer {
+            digest.failure_footer = failure_footer;
+        }
+        if let Some(fallbacks) = request.fallbacks {
+            digest.fallbacks = fallbacks;
+        }
+        let digest = digest.clone();
+
+        write_json(&self.path, &updated)?;
+        *digests = updated;
+        Ok(Some(digest))
+    }
+
+    pub fn delete(&self, id: &str) -> io::Result<bool> {
+        let mut digests = self.digests.write().unwrap();
+        let updated: Vec<Digest> = digests
+            .iter()
+            .filter(|digest| digest.id != id)
+            .cloned()
+            .collect();
+        if updated.len() == digests.len() {
+            return Ok(false);
+        }
+
+        write_json(&self.path, &updated)?;
+        *digests = updated;
+        Ok(true)
+    }
+}
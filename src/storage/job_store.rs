@@ -0,0 +1,173 @@
+use crate::models::{
+    digest_models::{Digest, ProvidersUsed},
+    job_models::{DigestProgress, DigestStage, Job, JobStatus, JobTrigger, StageTimings},
+};
+use actix_web::web;
+use chrono::{DateTime, Utc};
+use std::{collections::VecDeque, sync::RwLock};
+use uuid::Uuid;
+
+/// Number of finished jobs kept in memory for `GET /api/jobs/{id}`.
+pub const MAX_RETAINED_JOBS: usize = 200;
+
+/// Notification runs and their progress. Jobs are kept in memory only.
+#[derive(Default)]
+pub struct JobStore {
+    jobs: RwLock<VecDeque<Job>>,
+}
+
+impl JobStore {
+    pub fn create(&self, trigger: JobTrigger, digests: &[(Digest, Option<DateTime<Utc>>)]) -> Job {
+        let job = Job {
+            id: Uuid::new_v4().to_string(),
+            trigger,
+            status: JobStatus::Queued,
+            created_at: Utc::now(),
+            started_at: None,
+            finished_at: None,
+            duration_ms: None,
+            digests: digests
+                .iter()
+                .map(|(digest, scheduled_for)| DigestProgress {
+                    digest_id: digest.id.clone(),
+                    subject: digest.subject.clone(),
+                    scheduled_for: *scheduled_for,
+                    stage: DigestStage::Pending,
+                    queries_generated: 0,
+                    searches_completed: 0,
+                    searches_failed: 0,
+                    transformed: false,
+                    emailed: false,
+                    message_ids: Vec::new(),
+                    failed_recipients: Vec::new(),
+                    error: None,
+                    failures: Vec::new(),
+                    providers: ProvidersUsed::default(),
+                    started_at: None,
+                    finished_at: None,
+                    duration_ms: None,
+                    timings: StageTimings::default(),
+                })
+                .collect(),
+        };
+
+        let mut jobs = self.jobs.write().unwrap();
+        jobs.push_front(job.clone());
+        jobs.truncate(MAX_RETAINED_JOBS);
+        job
+    }
+
+    pub fn list(&self) -> Vec<Job> {
+        self.jobs.read().unwrap().iter().cloned().collect()
+    }
+
+    pub fn get(&self, id: &str) -> Option<Job> {
+        self.jobs
+            .read()
+            .unwrap()
+            .iter()
+            .find(|job| job.id == id)
+            .cloned()
+    }
+
+    pub fn start(&self, id: &str) {
+        self.update(id, |job| {
+            job.status = JobStatus::Running;
+            job.started_at = Some(Utc::now());
+        });
+    }
+
+    /// Marks the job finished, deriving its outcome from the digests it ran.
+    pub fn finish(&self, id: &str) {
+        self.update(id, |job| {
+            let finished_at = Utc::now();
+            let count = |stage| {
+                job.digests
+                    .iter()
+                    .filter(|digest| digest.stage == stage)
+                    .count()
+            };
+            let (failed, skipped) = (count(DigestStage::Failed), count(DigestStage::Skipped));
+            job.status = if skipped > 0 && skipped == job.digests.len() {
+                JobStatus::Skipped
+            } else if failed == 0
+                && job
+                    .digests
+                    .iter()
+                    .all(|digest| digest.failed_recipients.is_empty())
+            {
+                JobStatus::Succeeded
+            } else if failed + skipped == job.digests.len() {
+                JobStatus::Failed
+            } else {
+                JobStatus::PartiallyFailed
+            };
+            job.duration_ms = job
+                .started_at
+                .map(|started_at| (finished_at - started_at).num_milliseconds());
+            job.finished_at = Some(finished_at);
+        });
+    }
+
+    pub fn update_digest(
+        &self,
+        id: &str,
+        digest_id: &str,
+        change: impl FnOnce(&mut DigestProgress),
+    ) {
+        self.update(id, |job| {
+            if let Some(progress) = job
+                .digests
+                .iter_mut()
+                .find(|progress| progress.digest_id == digest_id)
+            {
+                change(progress);
+            }
+        });
+    }
+
+    fn update(&self, id: &str, change: impl FnOnce(&mut Job)) {
+        if let Some(job) = self
+            .jobs
+            .write()
+            .unwrap()
+            .iter_mut()
+            .find(|job| job.id == id)
+        {
+            change(job);
+        }
+    }
+}
+
+/// Reports pipeline progress for one digest within a job.
+#[derive(Clone)]
+pub struct DigestReporter {
+    job_store: Option<web::Data<JobStore>>,
+    job_id: String,
+    digest_id: String,
+}
+
+impl DigestReporter {
+    pub fn new(job_store: web::Data<JobStore>, job_id: &str, digest_id: &str) -> Self {
+        DigestReporter {
+            job_store: Some(job_store),
+            job_id: job_id.to_string(),
+            digest_id: digest_id.to_string(),
+        }
+    }
+
+    /// A reporter for pipeline runs that aren't tracked as a job, such as previews.
+    pub fn detached() -> Self {
+        DigestReporter {
+            job_store: None,
+            job_id: String::new(),
+            digest_id: String::new(),
+        }
+    }
+
+    pub fn update(&self, change: impl FnOnce(&mut DigestProgress)) {
+        if let Some(job_store) = &self.job_store {
+            job_store.update_digest(&self.job_id, &self.digest_id, change);
+        }
+    }
+}
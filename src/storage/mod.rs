@@ -0,0 +1,31 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fs, io, path::Path};
+use uuid::Uuid;
+
+pub mod digest_store;
+pub mod job_store;
+pub mod response_cache;
+pub mod run_store;
+
+/// Reads a JSON file, returning `None` when it has not been created yet.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> io::Result<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(path)?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Writes to a temporary file first so a crash mid-write never leaves a truncated file. Each
+/// write gets its own temporary file, so concurrent writers to one path don't clobber each other.
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let contents = serde_json::to_string_pretty(value)?;
+    let temp_path = path.with_extension(format!("{}.tmp", Uuid::new_v4()));
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, path)
+}
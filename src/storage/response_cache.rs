@@ -0,0 +1,272 @@
+use crate::{
+    constants::utility::{data_path, log_error},
+    storage::{read_json, write_json},
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha1::{Digest, Sha1};
+use std::{
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration as StdDuration, SystemTime},
+};
+
+pub const DEFAULT_SEARCH_TTL_SECONDS: i64 = 6 * 60 * 60;
+pub const DEFAULT_LLM_TTL_SECONDS: i64 = 7 * 24 * 60 * 60;
+const SWEEP_INTERVAL_SECONDS: i64 = 60 * 60;
+
+#[derive(Debug, Clone, Copy)]
+pub enum CallType {
+    Search,
+    Llm,
+}
+
+impl CallType {
+    fn dir_name(self) -> &'static str {
+        match self {
+            CallType::Search => "search",
+            CallType::Llm => "llm",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<T> {
+    created_at: DateTime<Utc>,
+    provider: String,
+    model: Option<String>,
+    response: T,
+}
+
+/// Provider responses keyed on a hash of the provider, base URL, model and request body, stored
+/// as one JSON file per entry under `$DATA_DIR/cache/{search,llm}`. Expired entries are replaced
+/// on the next identical call, and swept at most hourly when a new entry is written.
+pub struct ResponseCache {
+    dir: PathBuf,
+    search_ttl: Duration,
+    llm_ttl: Duration,
+    last_sweep: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl ResponseCache {
+    /// TTLs come from `CACHE_SEARCH_TTL_SECONDS` and `CACHE_LLM_TTL_SECONDS`, 0 disables caching
+    /// of that call type.
+    pub fn from_env() -> Self {
+        let ttl = |name: &str, default: i64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|seconds| seconds.parse::<i64>().ok())
+                .unwrap_or(default)
+        };
+        ResponseCache::new(
+            data_path("cache"),
+            Duration::seconds(ttl("CACHE_SEARCH_TTL_SECONDS", DEFAULT_SEARCH_TTL_SECONDS)),
+            Duration::seconds(ttl("CACHE_LLM_TTL_SECONDS", DEFAULT_LLM_TTL_SECONDS)),
+        )
+    }
+
+    /// A TTL of zero or less disables caching of that call type.
+    pub fn new(dir: PathBuf, search_ttl: Duration, llm_ttl: Duration) -> Self {
+        ResponseCache {
+            dir,
+            search_ttl,
+            llm_ttl,
+            last_sweep: Mutex::new(None),
+        }
+    }
+
+    pub fn key(
+        provider: &str,
+        base_url: Option<&str>,
+        model: Option<&str>,
+        body: &Value,
+    ) -> String {
+        let mut hasher = Sha1::new();
+        hasher.update(json!([provider, base_url, model, body]).to_string());
+        format!("{:x}", hasher.finalize())
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, call: CallType, key: &str) -> Option<T> {
+        if self.ttl(call) <= Duration::zero() {
+            return None;
+        }
+        let path = self.path(call, key);
+        match read_json::<CacheEntry<T>>(&path) {
+            Ok(Some(entry)) if Utc::now() - entry.created_at < self.ttl(call) => {
+                Some(entry.response)
+            }
+            Ok(_) => None,
+            Err(e) => {
+                log_error(&format!(
+                    "Ignoring unreadable cache entry {:?}: {}",
+                    path, e
+                ));
+                let _ = fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    pub fn put<T: Serialize>(
+        &self,
+        call: CallType,
+        key: &str,
+        provider: &str,
+        model: Option<&str>,
+        response: &T,
+    ) {
+        if self.ttl(call) <= Duration::zero() {
+            return;
+        }
+        let entry = CacheEntry {
+            created_at: Utc::now(),
+            provider: provider.to_string(),
+            model: model.map(|model| model.to_string()),
+            response,
+        };
+        if let Err(e) = write_json(&self.path(call, key), &entry) {
+            log_error(&format!("Failed to write cache entry: {}", e));
+        }
+        self.sweep_if_due(Utc::now());
+    }
+
+    fn sweep_if_due(&self, now: DateTime<Utc>) {
+        {
+            let mut last_sweep = self.last_sweep.lock().unwrap();
+            if last_sweep.is_some_and(|at| now - at < Duration::seconds(SWEEP_INTERVAL_SECONDS)) {
+                return;
+            }
+            *last_sweep = Some(now);
+        }
+        for call in [CallType::Search, CallType::Llm] {
+            self.sweep(call, now);
+        }
+    }
+
+    /// Removes entries, and temporary files left by interrupted writes, last written longer
+    /// than the TTL ago. With caching off for the call type, every file is removed.
+    fn sweep(&self, call: CallType, now: DateTime<Utc>) {
+        let ttl = self.ttl(call).to_std().unwrap_or(StdDuration::ZERO);
+        let Some(cutoff) = SystemTime::from(now).checked_sub(ttl) else {
+            return;
+        };
+        let Ok(entries) = fs::read_dir(self.dir.join(call.dir_name())) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let written = entry.metadata().and_then(|metadata| metadata.modified());
+            if written.is_ok_and(|written| written <= cutoff) {
+                if let Err(e) = fs::remove_file(entry.path()) {
+                    log_error(&format!(
+                        "Failed to remove expired cache entry {:?}: {}",
+                        entry.path(),
+                        e
+                    ));
+                }
+            }
+        }
+    }
+
+    fn ttl(&self, call: CallType) -> Duration {
+        match call {
+            CallType::Search => self.search_ttl,
+            CallType::Llm => self.llm_ttl,
+        }
+    }
+
+    fn path(&self, call: CallType, key: &str) -> PathBuf {
+        self.dir.join(call.dir_name()).join(format!("{}.json", key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use uuid::Uuid;
+
+    struct TempCache {
+        cache: ResponseCache,
+    }
+
+    impl TempCache {
+        fn new() -> Self {
+            TempCache {
+                cache: ResponseCache::new(
+                    std::env::temp_dir().join(format!("gimme-cache-{}", Uuid::new_v4())),
+                    Duration::hours(1),
+                    Duration::zero(),
+                ),
+            }
+        }
+
+        /// Writes a file into the search cache, last modified `age` ago.
+        fn write_aged(&self, name: &str, age: Duration) -> PathBuf {
+            let path = self.cache.dir.join("search").join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let file = File::create(&path).unwrap();
+            file.set_modified(SystemTime::from(Utc::now() - age))
+                .unwrap();
+            path
+        }
+    }
+
+    impl Drop for TempCache {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.cache.dir);
+        }
+    }
+
+    #[test]
+    fn keys_differ_by_base_url() {
+        let body = json!({ "prompt": "hello" });
+        let key = |base_url| ResponseCache::key("local", base_url, Some("llama3"), &body);
+        assert_ne!(
+            key(Some("http://localhost:11434/v1")),
+            key(Some("http://localhost:8080/v1"))
+        );
+        assert_eq!(
+            key(Some("http://localhost:11434/v1")),
+            key(Some("http://localhost:11434/v1"))
+        );
+    }
+
+    #[test]
+    fn serves_entries_until_they_expire() {
+        let temp = TempCache::new();
+        temp.cache
+            .put(CallType::Search, "fresh", "brave", None, &"response");
+        assert_eq!(
+            temp.cache
+                .get::<String>(CallType::Search, "fresh")
+                .as_deref(),
+            Some("response")
+        );
+
+        temp.cache
+            .put(CallType::Llm, "off", "openai", None, &"reply");
+        assert_eq!(temp.cache.get::<String>(CallType::Llm, "off"), None);
+    }
+
+    #[test]
+    fn sweeps_expired_files_at_most_hourly() {
+        let temp = TempCache::new();
+        let fresh = temp.write_aged("fresh.json", Duration::minutes(5));
+        let expired = temp.write_aged("expired.json", Duration::hours(2));
+        let abandoned = temp.write_aged("expired.0a1b.tmp", Duration::hours(2));
+
+        let now = Utc::now();
+        temp.cache.sweep_if_due(now);
+        assert!(fresh.exists());
+        assert!(!expired.exists());
+        assert!(!abandoned.exists());
+
+        let expired = temp.write_aged("expired.json", Duration::hours(2));
+        temp.cache.sweep_if_due(now + Duration::minutes(30));
+        assert!(expired.exists());
+        temp.cache.sweep_if_due(now + Duration::hours(1));
+        assert!(!expired.exists());
+    }
+}
@@ -0,0 +1,328 @@
+use crate::{
+    models::digest_models::{DigestRunState, DigestSent, RunClaim},
+    storage::{read_json, write_json},
+};
+use chrono::{DateTime, Duration, Utc};
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io,
+    path::PathBuf,
+    sync::RwLock,
+};
+use uuid::Uuid;
+
+/// Number of sent occurrences remembered per digest.
+pub const MAX_SENT_OCCURRENCES: usize = 50;
+/// How long a claim holds before it's treated as left behind by a crashed process.
+pub const DEFAULT_CLAIM_TTL_MINUTES: i64 = 120;
+
+/// Per-digest run state keyed by digest id, persisted as a JSON file.
+///
+/// Also guards against duplicate sends: a digest can only be claimed by one run at a time,
+/// and a scheduled occurrence that was already sent can't be claimed again unless forced.
+/// Claims are written to the file under an exclusive lock on `<file>.lock`, re-reading it
+/// first, so the guard holds across restarts and between processes sharing the same
+/// `DATA_DIR`. Machines with separate volumes aren't coordinated. A claim left by a process
+/// that died mid-run expires after `RUN_CLAIM_TTL_MINUTES`.
+pub struct RunStore {
+    path: PathBuf,
+    runs: RwLock<HashMap<String, DigestRunState>>,
+}
+
+impl RunStore {
+    pub fn load(path: PathBuf) -> io::Result<Self> {
+        let runs = read_json(&path)?.unwrap_or_default();
+        Ok(RunStore {
+            path,
+            runs: RwLock::new(runs),
+        })
+    }
+
+    pub fn get(&self, digest_id: &str) -> Option<DigestRunState> {
+        self.runs.read().unwrap().get(digest_id).cloned()
+    }
+
+    /// Claims a run of the digest and returns the token that releases it. `force` skips both
+    /// the overlap and already-sent checks, but the forced run still holds its own claim.
+    pub fn claim(
+        &self,
+        digest_id: &str,
+        occurrence: Option<DateTime<Utc>>,
+        force: bool,
+    ) -> Result<String, String> {
+        let ttl = std::env::var("RUN_CLAIM_TTL_MINUTES")
+            .ok()
+            .and_then(|minutes| minutes.parse::<i64>().ok())
+            .unwrap_or(DEFAULT_CLAIM_TTL_MINUTES);
+        let now = Utc::now();
+        let token = Uuid::new_v4().to_string();
+        let mut refused = None;
+        self.modify(digest_id, |state| {
+            state
+                .claims
+                .retain(|claim| now - claim.claimed_at < Duration::minutes(ttl));
+            if !force {
+                if !state.claims.is_empty() {
+                    refused = Some("A run of this digest is already in progress".to_string());
+                    return;
+                }
+                if let Some(occurrence) =
+                    occurrence.filter(|occurrence| state.sent_occurrences.contains(occurrence))
+                {
+                    refused = Some(format!("Occurrence {} was already sent", occurrence));
+                    return;
+                }
+            }
+            state.claims.push(RunClaim {
+                token: token.clone(),
+                occurrence,
+                claimed_at: now,
+            });
+        })
+        .map_err(|e| format!("Failed to save run claim: {}", e))?;
+        match refused {
+            Some(reason) => Err(reason),
+            None => Ok(token),
+        }
+    }
+
+    /// Records a successful run, the providers it used and any recipients it couldn't reach,
+    /// and releases the run's claim.
+    pub fn record_success(
+        &self,
+        digest_id: &str,
+        token: &str,
+        occurrence: Option<DateTime<Utc>>,
+        sent: &DigestSent,
+        at: DateTime<Utc>,
+    ) -> io::Result<()> {
+        self.modify(digest_id, |state| {
+            release(state, token);
+            state.last_success_at = Some(at);
+            state.last_failure_at = None;
+            state.last_error = None;
+            state.last_providers = Some(sent.providers.clone());
+            state.last_failed_recipients = sent.failed_recipients.clone();
+            if let Some(occurrence) = occurrence {
+                state.last_occurrence = state.last_occurrence.max(Some(occurrence));
+                if !state.sent_occurrences.contains(&occurrence) {
+                    state.sent_occurrences.push(occurrence);
+                }
+                state.sent_occurrences.sort();
+                let excess = state
+                    .sent_occurrences
+                    .len()
+                    .saturating_sub(MAX_SENT_OCCURRENCES);
+                state.sent_occurrences.drain(..excess);
+            }
+        })
+    }
+
+    /// Records a failed run and releases the run's claim. Only a failed scheduled occurrence
+    /// sets `last_failure_at`, which delays the scheduler's retry, so a failing on-demand run
+    /// never holds back the schedule.
+    pub fn record_failure(
+        &self,
+        digest_id: &str,
+        token: &str,
+        occurrence: Option<DateTime<Utc>>,
+        error: &str,
+        at: DateTime<Utc>,
+    ) -> io::Result<()> {
+        self.modify(digest_id, |state| {
+            release(state, token);
+            if occurrence.is_some() {
+                state.last_failure_at = Some(at);
+            }
+            state.last_error = Some(error.to_string());
+        })
+    }
+
+    /// Applies `change` to the digest's state as currently saved, holding the file lock
+    /// until the result is written.
+    fn modify(&self, digest_id: &str, change: impl FnOnce(&mut DigestRunState)) -> io::Result<()> {
+        let mut runs = self.runs.write().unwrap();
+        let lock = self.lock()?;
+        let mut updated: HashMap<String, DigestRunState> =
+            read_json(&self.path)?.unwrap_or_default();
+        change(updated.entry(digest_id.to_string()).or_default());
+        write_json(&self.path, &updated)?;
+        drop(lock);
+        *runs = updated;
+        Ok(())
+    }
+
+    /// Blocks until this process holds the exclusive lock, released when the file is dropped.
+    fn lock(&self) -> io::Result<File> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.path.with_extension("json.lock"))?;
+        file.lock()?;
+        Ok(file)
+    }
+}
+
+fn release(state: &mut DigestRunState, token: &str) {
+    state.claims.retain(|claim| claim.token != token);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::digest_models::DigestSent;
+    use chrono::TimeZone;
+
+    struct TempStore {
+        dir: PathBuf,
+        store: RunStore,
+    }
+
+    impl TempStore {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("gimme-runs-{}", Uuid::new_v4()));
+            let store = RunStore::load(dir.join("runs.json")).unwrap();
+            TempStore { dir, store }
+        }
+
+        fn reload(&self) -> RunStore {
+            RunStore::load(self.dir.join("runs.json")).unwrap()
+        }
+    }
+
+    impl Drop for TempStore {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn occurrence() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 19, 20, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn refuses_a_second_claim_until_released() {
+        let temp = TempStore::new();
+        let token = temp.store.claim("digest", None, false).unwrap();
+        assert!(temp.store.claim("digest", None, false).is_err());
+        assert!(temp.store.claim("other", None, false).is_ok());
+
+        temp.store
+            .record_failure("digest", &token, None, "failed", Utc::now())
+            .unwrap();
+        assert!(temp.store.claim("digest", None, false).is_ok());
+    }
+
+    #[test]
+    fn claims_are_shared_through_the_file() {
+        let temp = TempStore::new();
+        temp.store.claim("digest", None, false).unwrap();
+        assert!(temp.reload().claim("digest", None, false).is_err());
+    }
+
+    #[test]
+    fn refuses_an_occurrence_that_was_already_sent() {
+        let temp = TempStore::new();
+        let token = temp
+            .store
+            .claim("digest", Some(occurrence()), false)
+            .unwrap();
+        temp.store
+            .record_success(
+                "digest",
+                &token,
+                Some(occurrence()),
+                &DigestSent::default(),
+                Utc::now(),
+            )
+            .unwrap();
+
+        let refused = temp.store.claim("digest", Some(occurrence()), false);
+        assert_eq!(
+            refused.unwrap_err(),
+            format!("Occurrence {} was already sent", occurrence())
+        );
+        let state = temp.reload().get("digest").unwrap();
+        assert_eq!(state.sent_occurrences, [occurrence()]);
+        assert!(state.claims.is_empty());
+    }
+
+    #[test]
+    fn success_clears_the_last_failure() {
+        let temp = TempStore::new();
+        let failed_at = Utc.with_ymd_and_hms(2026, 10, 19, 20, 5, 0).unwrap();
+        let token = temp
+            .store
+            .claim("digest", Some(occurrence()), false)
+            .unwrap();
+        temp.store
+            .record_failure("digest", &token, Some(occurrence()), "failed", failed_at)
+            .unwrap();
+        let state = temp.store.get("digest").unwrap();
+        assert_eq!(state.last_failure_at, Some(failed_at));
+        assert_eq!(state.last_error.as_deref(), Some("failed"));
+
+        let token = temp
+            .store
+            .claim("digest", Some(occurrence()), false)
+            .unwrap();
+        let sent_at = failed_at + Duration::minutes(15);
+        temp.store
+            .record_success(
+                "digest",
+                &token,
+                Some(occurrence()),
+                &DigestSent::default(),
+                sent_at,
+            )
+            .unwrap();
+        let state = temp.reload().get("digest").unwrap();
+        assert_eq!(state.last_success_at, Some(sent_at));
+        assert_eq!(state.last_failure_at, None);
+        assert_eq!(state.last_error, None);
+    }
+
+    #[test]
+    fn forced_runs_hold_their_own_claim() {
+        let temp = TempStore::new();
+        let token = temp.store.claim("digest", None, false).unwrap();
+        let forced = temp.store.claim("digest", None, true).unwrap();
+        assert_ne!(token, forced);
+
+        temp.store
+            .record_failure("digest", &forced, None, "failed", Utc::now())
+            .unwrap();
+        let claims = temp.store.get("digest").unwrap().claims;
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[0].token, token);
+        assert!(temp.store.claim("digest", None, false).is_err());
+    }
+
+    #[test]
+    fn expired_claims_are_dropped() {
+        let temp = TempStore::new();
+        let state = DigestRunState {
+            claims: vec![RunClaim {
+                token: "crashed".to_string(),
+                occurrence: None,
+                claimed_at: Utc::now() - Duration::minutes(DEFAULT_CLAIM_TTL_MINUTES + 1),
+            }],
+            ..DigestRunState::default()
+        };
+        write_json(
+            &temp.dir.join("runs.json"),
+            &HashMap::from([("digest".to_string(), state)]),
+        )
+        .unwrap();
+
+        let token = temp.store.claim("digest", None, false).unwrap();
+        let claims = temp.store.get("digest").unwrap().claims;
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[0].token, token);
+    }
+}